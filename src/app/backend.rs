@@ -2,22 +2,33 @@ use std::sync::Arc;
 
 use anyhow::{Error, Result};
 use async_trait::async_trait;
-use tokio::sync::oneshot;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::{mpsc, oneshot};
 
+use super::services::auth::commands::CommandsFactory as AuthServiceCommandsFactory;
+use super::services::auth::entities::{validate_oauth2_profile_name, AccessToken};
+use super::services::auth::facade::AuthServiceFacade;
+use super::services::auth::service::AuthServiceInstance;
 use super::services::files::facade::FileServiceFacade;
 use super::services::files::service::FileServiceInstance;
-use super::services::web_client::commands::CommandsFactory as WebClientCommandsFactory;
-use super::services::web_client::entities::Response;
+use super::services::web_client::commands::{
+    CommandWebClient, CommandsFactory as WebClientCommandsFactory,
+};
+use super::services::web_client::downloads::DownloadTarget;
+use super::services::web_client::entities::{FailedAttempt, Response};
 use super::services::web_client::facade::WebClientFacade;
 use super::services::web_client::service::WebClientInstance;
-use crate::app::service_commands::Command;
+use crate::app::service_commands::{Command, Functor};
 use crate::app::service_runner::ServiceRunner;
 use crate::app::services::files::commands::CommandsFactory as FileServiceCommandsFactory;
 use crate::app::services::request::commands::CommandsFactory as RequestServCommandsFactory;
+use crate::app::services::request::entities::auth::{Auth, AuthType};
 use crate::app::services::request::entities::requests::RequestData;
+use crate::app::services::request::entities::sessions::Session;
 use crate::app::services::request::facade::RequestServiceFacade;
 use crate::app::services::request::service::RequestServiceInstance;
 use crate::utils::files as file_utils;
+use crate::utils::time::unix_now;
 use crate::utils::uuid::UUID;
 
 #[async_trait]
@@ -29,9 +40,18 @@ pub trait Backend: Send {
     async fn undo_request(&mut self, id: UUID) -> Result<()>;
     async fn redo_request(&mut self, id: UUID) -> Result<()>;
     async fn submit_request_blocking(&mut self, id: UUID) -> Result<Response>;
+    /// Submits the request in background. Each of its failed attempts is sent to
+    /// `attempts`, if given, as soon as it fails
     async fn submit_request_async(
         &mut self,
         id: UUID,
+        attempts: Option<UnboundedSender<FailedAttempt>>,
+    ) -> Result<oneshot::Receiver<Result<Response>>>;
+    async fn download_request_async(
+        &mut self,
+        id: UUID,
+        target: DownloadTarget,
+        attempts: Option<UnboundedSender<FailedAttempt>>,
     ) -> Result<oneshot::Receiver<Result<Response>>>;
 
     async fn save_request_datas_as(
@@ -43,12 +63,17 @@ pub trait Backend: Send {
     async fn find_all_request_name(&mut self) -> Result<Vec<String>>;
     async fn remove_request_saved(&mut self, name: String) -> Result<()>;
     async fn rename_request_saved(&mut self, request_name: String, new_name: String) -> Result<()>;
+
+    /// Session of the given name, empty if it does not exist yet
+    async fn get_session(&mut self, name: String) -> Result<Session>;
+    async fn save_session(&mut self, name: String, session: Session) -> Result<()>;
 }
 
 pub struct AppBackend {
     request_service: ServiceRunner<RequestServiceInstance>,
     web_client: ServiceRunner<WebClientInstance>,
     file_service: ServiceRunner<FileServiceInstance>,
+    auth_service: ServiceRunner<AuthServiceInstance>,
 }
 
 impl AppBackend {
@@ -56,6 +81,7 @@ impl AppBackend {
         request_service: impl RequestServiceFacade + 'static,
         web_client: impl WebClientFacade + 'static,
         file_service: impl FileServiceFacade + 'static,
+        auth_service: impl AuthServiceFacade + 'static,
     ) -> Self {
         let request_service = {
             let request_service = Box::new(request_service);
@@ -72,14 +98,127 @@ impl AppBackend {
             ServiceRunner::<FileServiceInstance>::from(file_service, "FileService")
         };
 
+        let auth_service = {
+            let auth_service = Box::new(auth_service);
+            ServiceRunner::<AuthServiceInstance>::from(auth_service, "AuthService")
+        };
+
         Self {
             request_service,
             web_client,
             file_service,
+            auth_service,
+        }
+    }
+
+    /// Shuts down every service, letting the commands already sent to them finish
+    pub async fn close(self) {
+        tokio::join!(
+            self.request_service.close(),
+            self.web_client.close(),
+            self.file_service.close(),
+            self.auth_service.close(),
+        );
+    }
+
+    /// Submits the request with the command given, getting the token of its OAuth2 profile
+    /// before. It runs in background, as the token may be refreshed after the response
+    fn submit_with_oauth2(
+        &self,
+        request: RequestData,
+        profile_name: String,
+        submit: impl Fn(RequestData) -> CommandWebClient<Result<Response>> + Send + Sync + 'static,
+    ) -> oneshot::Receiver<Result<Response>> {
+        let tokens = OAuth2Tokens {
+            auth_service: self.auth_service.command_channel.clone(),
+            file_service: self.file_service.command_channel.clone(),
+        };
+        let web_client = self.web_client.command_channel.clone();
+        let with_token = move |token: String| {
+            request
+                .clone()
+                .with_auth(Auth::init(AuthType::Bearer, token))
+        };
+
+        let (mut tx, rx) = oneshot::channel();
+        tokio::task::spawn(async move {
+            let response = async {
+                let (token, is_new) = tokens.get(&profile_name, false).await?;
+                let response =
+                    send_command_waiting_response(&web_client, submit(with_token(token))).await??;
+
+                // Token may be revoked before expiring, so it is renewed once
+                if response.status != 401 || is_new {
+                    return Ok(response);
+                }
+                let (token, _) = tokens.get(&profile_name, true).await?;
+                send_command_waiting_response(&web_client, submit(with_token(token))).await?
+            };
+            // Dropping the submit in progress aborts it, when the receiver is dropped
+            tokio::select! {
+                response = response => tx.send(response).ok(),
+                _ = tx.closed() => None,
+            };
+        });
+        rx
+    }
+}
+
+/// Access to tokens of OAuth2 profiles, reusing the ones cached at data directory
+struct OAuth2Tokens {
+    auth_service: mpsc::Sender<Functor<AuthServiceInstance>>,
+    file_service: mpsc::Sender<Functor<FileServiceInstance>>,
+}
+
+impl OAuth2Tokens {
+    /// Token of profile, and if it was just fetched. `renew` ignores the cached one
+    async fn get(&self, profile_name: &str, renew: bool) -> Result<(String, bool)> {
+        // No cache file is created for a profile which does not exist
+        validate_oauth2_profile_name(profile_name)?;
+        let has_profile = send_command_waiting_response(
+            &self.auth_service,
+            AuthServiceCommandsFactory::has_profile(profile_name.into()),
+        )
+        .await?;
+        if !has_profile {
+            return Err(Error::msg(format!(
+                "OAuth2 profile '{profile_name}' is not defined at config file"
+            )));
+        }
+
+        let path = send_command_waiting_response(
+            &self.file_service,
+            FileServiceCommandsFactory::get_or_create_file_of_oauth2_token(profile_name.into()),
+        )
+        .await??;
+
+        if !renew {
+            let cached = file_utils::read_from_file(path.clone()).await?;
+            let cached = serde_json::from_str::<AccessToken>(&cached).ok();
+            if let Some(token) = cached.filter(|token| !token.is_expired(unix_now())) {
+                return Ok((token.access_token, false));
+            }
         }
+
+        let token = send_command_waiting_response(
+            &self.auth_service,
+            AuthServiceCommandsFactory::fetch_token(profile_name.into()),
+        )
+        .await??;
+        file_utils::write_to_file(path, &serde_json::to_string(&token)?).await?;
+        Ok((token.access_token, true))
     }
 }
 
+/// Name of OAuth2 profile of the request, if it uses one
+fn oauth2_profile_of(request: &RequestData) -> Option<String> {
+    request
+        .auth
+        .as_ref()
+        .filter(|auth| auth.auth_type == AuthType::OAuth2)
+        .map(|auth| auth.credentials.clone())
+}
+
 #[async_trait]
 impl Backend for AppBackend {
     async fn add_request(&mut self, request: RequestData) -> Result<UUID> {
@@ -137,9 +276,17 @@ impl Backend for AppBackend {
             .await?
             .ok_or(Error::msg("Not found request to given ID"))?;
 
+        if let Some(profile_name) = oauth2_profile_of(&request_data) {
+            let response =
+                self.submit_with_oauth2((*request_data).clone(), profile_name, |request| {
+                    WebClientCommandsFactory::submit(request, None)
+                });
+            return response.await?;
+        }
+
         let resp = run_command_waiting_response(
             &self.web_client,
-            WebClientCommandsFactory::submit((*request_data).clone()),
+            WebClientCommandsFactory::submit((*request_data).clone(), None),
         )
         .await?;
         Ok(resp.unwrap())
@@ -148,12 +295,48 @@ impl Backend for AppBackend {
     async fn submit_request_async(
         &mut self,
         id: UUID,
+        attempts: Option<UnboundedSender<FailedAttempt>>,
     ) -> Result<oneshot::Receiver<Result<Response>>> {
         let request_data = self.get_request(id).await?.unwrap();
+        if let Some(profile_name) = oauth2_profile_of(&request_data) {
+            return Ok(self.submit_with_oauth2(
+                (*request_data).clone(),
+                profile_name,
+                move |request| WebClientCommandsFactory::submit(request, attempts.clone()),
+            ));
+        }
+
+        let Command {
+            command_fn,
+            response,
+        } = WebClientCommandsFactory::submit((*request_data).clone(), attempts);
+        self.web_client.command_channel.send(command_fn).await?;
+        Ok(response.unwrap())
+    }
+    async fn download_request_async(
+        &mut self,
+        id: UUID,
+        target: DownloadTarget,
+        attempts: Option<UnboundedSender<FailedAttempt>>,
+    ) -> Result<oneshot::Receiver<Result<Response>>> {
+        let request_data = self
+            .get_request(id)
+            .await?
+            .ok_or(Error::msg("Not found request to given ID"))?;
+        if let Some(profile_name) = oauth2_profile_of(&request_data) {
+            return Ok(self.submit_with_oauth2(
+                (*request_data).clone(),
+                profile_name,
+                move |request| {
+                    WebClientCommandsFactory::download(request, target.clone(), attempts.clone())
+                },
+            ));
+        }
+
         let Command {
             command_fn,
             response,
-        } = WebClientCommandsFactory::submit((*request_data).clone());
+        } = WebClientCommandsFactory::download((*request_data).clone(), target, attempts);
         self.web_client.command_channel.send(command_fn).await?;
         Ok(response.unwrap())
     }
@@ -222,6 +405,34 @@ impl Backend for AppBackend {
         )
         .await?
     }
+
+    async fn get_session(&mut self, name: String) -> Result<Session> {
+        let path = run_command_waiting_response(
+            &self.file_service,
+            FileServiceCommandsFactory::get_or_create_file_of_session(name.clone()),
+        )
+        .await??;
+
+        let session = file_utils::read_from_file(path).await?;
+        if session.is_empty() {
+            return Ok(Session::default());
+        }
+
+        serde_json::from_str(&session)
+            .map_err(|err| Error::msg(format!("Invalid data of session '{name}': {err}")))
+    }
+
+    async fn save_session(&mut self, name: String, session: Session) -> Result<()> {
+        let path = run_command_waiting_response(
+            &self.file_service,
+            FileServiceCommandsFactory::get_or_create_file_of_session(name),
+        )
+        .await??;
+
+        let session = serde_json::to_string(&session)?;
+        file_utils::write_to_file(path, &session).await?;
+        Ok(())
+    }
 }
 
 async fn run_commands<Service, Resp>(
@@ -241,6 +452,16 @@ async fn run_command_waiting_response<Service, Resp>(
     service: &ServiceRunner<Service>,
     command: Command<Service, Resp>,
 ) -> Result<Resp>
+where
+    Service: Send + 'static,
+{
+    send_command_waiting_response(&service.command_channel, command).await
+}
+
+async fn send_command_waiting_response<Service, Resp>(
+    command_channel: &mpsc::Sender<Functor<Service>>,
+    command: Command<Service, Resp>,
+) -> Result<Resp>
 where
     Service: Send + 'static,
 {
@@ -249,7 +470,7 @@ where
         response,
     } = command;
 
-    service.command_channel.send(command_fn).await?;
+    command_channel.send(command_fn).await?;
 
     match response {
         Some(response_listener) => Ok(response_listener.await?),
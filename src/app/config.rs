@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use serde::Deserialize;
+
+use crate::app::services::auth::entities::OAuth2Profile;
+use crate::app::services::request::entities::proxies::ProxySettings;
+use crate::app::services::request::entities::redirects::RedirectPolicy;
+use crate::app::services::request::entities::timeouts::{millis_from_secs, Timeouts};
+use crate::app::services::request::entities::tls::TlsSettings;
+use crate::app::services::web_client::entities::HttpClientConfig;
+
+pub const CONFIG_FILE: &str = "config.json";
+
+/// User preferences read from `config.json`, at the config directory.
+/// Every field is optional, an empty file means all defaults.
+#[derive(Default, Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AppConfig {
+    /// Default timeouts, in seconds, of requests which do not define their own
+    pub timeout: Option<f64>,
+    pub connect_timeout: Option<f64>,
+    pub read_timeout: Option<f64>,
+
+    /// Follow redirects by default
+    pub follow: Option<bool>,
+    pub max_redirects: Option<usize>,
+
+    /// TLS settings, with paths of PEM files
+    pub verify: Option<bool>,
+    pub ca_bundle: Option<String>,
+    pub cert: Option<String>,
+    pub cert_key: Option<String>,
+
+    /// Proxies by scheme of URL, over the ones of environment variables
+    pub http_proxy: Option<String>,
+    pub https_proxy: Option<String>,
+    pub no_proxy: Option<String>,
+
+    /// OAuth2 profiles by name, used by requests with 'oauth2' auth
+    pub oauth2: HashMap<String, OAuth2Profile>,
+}
+
+impl AppConfig {
+    pub fn from_json(content: &str) -> Result<Self> {
+        if content.trim().is_empty() {
+            return Ok(AppConfig::default());
+        }
+        Ok(serde_json::from_str(content)?)
+    }
+
+    pub fn http_client_config(&self) -> Result<HttpClientConfig> {
+        let timeouts = Timeouts {
+            total_ms: self.timeout.map(millis_from_secs).transpose()?,
+            connect_ms: self.connect_timeout.map(millis_from_secs).transpose()?,
+            read_ms: self.read_timeout.map(millis_from_secs).transpose()?,
+        };
+
+        let redirects = RedirectPolicy {
+            follow: self.follow,
+            max_redirects: self.max_redirects,
+        };
+
+        let tls = TlsSettings {
+            verify: self.verify,
+            ca_bundle: self.ca_bundle.clone(),
+            cert: self.cert.clone(),
+            cert_key: self.cert_key.clone(),
+        };
+
+        let proxy = ProxySettings::from_env().be_overwrite_by(ProxySettings {
+            http: self.http_proxy.clone(),
+            https: self.https_proxy.clone(),
+            no_proxy: self.no_proxy.clone(),
+        });
+
+        Ok(HttpClientConfig::default()
+            .with_timeouts(timeouts)
+            .with_redirects(redirects)
+            .with_tls(tls)
+            .with_proxy(proxy))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_config_file() {
+        assert_eq!(AppConfig::default(), AppConfig::from_json("").unwrap());
+        assert_eq!(AppConfig::default(), AppConfig::from_json("{}").unwrap());
+    }
+
+    #[test]
+    fn test_config_timeouts_to_http_client_config() {
+        let config = AppConfig::from_json(r#"{ "timeout": 30, "connect_timeout": 2.5 }"#).unwrap();
+
+        assert_eq!(
+            Timeouts::default()
+                .with_total_ms(30_000)
+                .with_connect_ms(2_500),
+            config.http_client_config().unwrap().timeouts,
+        );
+    }
+
+    #[test]
+    fn test_config_oauth2_profiles() {
+        let config = AppConfig::from_json(
+            r#"{ "oauth2": { "api": { "token_url": "https://auth.com/token", "client_id": "app", "client_secret": "secret" } } }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            Some(&OAuth2Profile {
+                token_url: "https://auth.com/token".into(),
+                client_id: "app".into(),
+                client_secret: "secret".into(),
+                scopes: vec![],
+            }),
+            config.oauth2.get("api")
+        );
+    }
+
+    #[test]
+    fn test_invalid_config_file() {
+        assert!(AppConfig::from_json(r#"{ "timeout": -1 }"#)
+            .and_then(|config| config.http_client_config())
+            .is_err());
+        assert!(AppConfig::from_json(r#"{ "unknown": 1 }"#).is_err());
+    }
+}
@@ -1,4 +1,5 @@
 pub mod backend;
+pub mod config;
 pub mod service_commands;
 pub mod service_runner;
 pub mod services;
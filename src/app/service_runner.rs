@@ -22,7 +22,9 @@ impl<ServiceInstance: Send + 'static> ServiceRunner<ServiceInstance> {
         let runner_thread = tokio::task::spawn(async move {
             let mut rx_shutdown_channel = rx_shutdown_channel;
             loop {
+                // Commands already sent run before a shutdown
                 tokio::select! {
+                    biased;
                     Some(command_fn) = rx_command_channel.recv() => {
                         service_instance = command_fn(service_instance);
                     },
@@ -44,9 +46,9 @@ impl<ServiceInstance: Send + 'static> ServiceRunner<ServiceInstance> {
         }
     }
 
-    pub fn close(self) -> Option<()> {
+    /// Stops the service after the command it is running, waiting for it
+    pub async fn close(self) -> Option<()> {
         self.shutdown_channel.send(()).ok()?;
-        self.runner_thread.abort();
-        Some(())
+        self.runner_thread.await.ok()
     }
 }
@@ -0,0 +1,441 @@
+use std::path::PathBuf;
+
+use anyhow::Error;
+use hmac::{Hmac, Mac};
+use percent_encoding::percent_decode_str;
+use sha2::{Digest, Sha256};
+
+use crate::app::services::request::entities::auth::Auth;
+use crate::app::services::request::entities::methods::METHODS;
+use crate::app::services::request::entities::requests::{BodyPayload, RequestData};
+
+const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+
+/// Headers which may be changed after signing, so they are not signed
+const UNSIGNED_HEADERS: [&str; 2] = ["authorization", "cookie"];
+
+/// Keys of an AWS account, or of an S3-compatible service like MinIO
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AwsCredentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+impl AwsCredentials {
+    /// Credentials of the given profile at the shared credentials file (`~/.aws/credentials`).
+    /// Without one, the environment variables are used before the profile of 'AWS_PROFILE'
+    pub fn load(profile: Option<&str>) -> anyhow::Result<Self> {
+        let get_var = |name: &str| std::env::var(name).ok().filter(|value| !value.is_empty());
+
+        if let Some(credentials) = profile
+            .is_none()
+            .then(|| Self::from_vars(get_var))
+            .flatten()
+        {
+            return Ok(credentials);
+        }
+
+        let profile = profile
+            .map(String::from)
+            .or_else(|| get_var("AWS_PROFILE"))
+            .unwrap_or("default".to_string());
+        let path = get_var("AWS_SHARED_CREDENTIALS_FILE")
+            .map(PathBuf::from)
+            .or_else(|| {
+                let home = directories::BaseDirs::new()?.home_dir().to_path_buf();
+                Some(home.join(".aws").join("credentials"))
+            })
+            .ok_or(Error::msg("No possible to find the AWS credentials file"))?;
+
+        std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| Self::from_credentials_file(&content, &profile))
+            .ok_or(Error::msg(format!(
+                "No AWS credentials found, set 'AWS_ACCESS_KEY_ID' and 'AWS_SECRET_ACCESS_KEY' or the profile '{profile}' at '{}'",
+                path.display()
+            )))
+    }
+
+    pub fn from_vars(get_var: impl Fn(&str) -> Option<String>) -> Option<Self> {
+        Some(Self {
+            access_key_id: get_var("AWS_ACCESS_KEY_ID")?,
+            secret_access_key: get_var("AWS_SECRET_ACCESS_KEY")?,
+            session_token: get_var("AWS_SESSION_TOKEN"),
+        })
+    }
+
+    /// Credentials of a profile at a file like
+    /// ```ini
+    /// [default]
+    /// aws_access_key_id = AKIA...
+    /// aws_secret_access_key = ...
+    /// ```
+    pub fn from_credentials_file(content: &str, profile: &str) -> Option<Self> {
+        let mut current_profile = None;
+        let mut values = Vec::new();
+
+        for line in content.lines().map(str::trim) {
+            if line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                current_profile = Some(name.trim());
+                continue;
+            }
+            if current_profile == Some(profile) {
+                if let Some((key, value)) = line.split_once('=') {
+                    values.push((key.trim(), value.trim()));
+                }
+            }
+        }
+
+        let value = |key: &str| {
+            values
+                .iter()
+                .find(|(value_key, _)| value_key.eq_ignore_ascii_case(key))
+                .map(|(_, value)| value.to_string())
+        };
+
+        Some(Self {
+            access_key_id: value("aws_access_key_id")?,
+            secret_access_key: value("aws_secret_access_key")?,
+            session_token: value("aws_session_token"),
+        })
+    }
+}
+
+/// Signs requests with AWS Signature Version 4, for a region and service like
+/// 'us-east-1' and 's3'
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AwsSigner {
+    pub credentials: AwsCredentials,
+    pub region: String,
+    pub service: String,
+}
+
+impl AwsSigner {
+    /// Signer of an auth with credentials like 'REGION:SERVICE' or 'REGION:SERVICE:PROFILE'
+    pub fn from_auth(auth: &Auth) -> anyhow::Result<Self> {
+        let mut parts = auth.credentials.split(':').map(str::trim);
+
+        match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(region), Some(service), profile, None)
+                if !region.is_empty() && !service.is_empty() =>
+            {
+                Ok(Self {
+                    credentials: AwsCredentials::load(profile.filter(|p| !p.is_empty()))?,
+                    region: region.to_string(),
+                    service: service.to_string(),
+                })
+            }
+            _ => Err(Error::msg(format!(
+                "Invalid AWS auth '{}', it must be like 'REGION:SERVICE' or 'REGION:SERVICE:PROFILE'",
+                auth.credentials
+            ))),
+        }
+    }
+
+    /// Adds the signature headers to the request, as it will be sent. So it must not be
+    /// changed after signing, and its body files must be already read. `unix_time` is
+    /// the time of signature, in seconds
+    pub fn sign(&self, mut request: RequestData, unix_time: u64) -> anyhow::Result<RequestData> {
+        if !request.body_files.is_empty() {
+            return Err(Error::msg(
+                "Body files of request must be read before signing it",
+            ));
+        }
+        let url = url::Url::parse(&request.url.to_string())?;
+        let datetime = amz_datetime(unix_time);
+        let date = &datetime[..8];
+
+        // Same body sent by the HTTP repository. Multipart boundaries are only known then
+        let body = request.body.as_ref().filter(|body| {
+            !body.is_empty() || !matches!(request.method, METHODS::GET | METHODS::HEAD)
+        });
+        let payload_hash = match body {
+            Some(BodyPayload::Multipart(_)) => "UNSIGNED-PAYLOAD".to_string(),
+            Some(body) => match request.body_compression() {
+                Some(compression) => hex(&Sha256::digest(
+                    compression.compress(body.to_string().as_bytes())?,
+                )),
+                None => hex(&Sha256::digest(body.to_string())),
+            },
+            None => hex(&Sha256::digest("")),
+        };
+
+        let mut signature_headers = vec![("X-Amz-Date", datetime.clone())];
+        if self.service == "s3" {
+            signature_headers.push(("X-Amz-Content-Sha256", payload_hash.clone()));
+        }
+        if let Some(session_token) = &self.credentials.session_token {
+            signature_headers.push(("X-Amz-Security-Token", session_token.clone()));
+        }
+        for (key, value) in signature_headers {
+            request
+                .headers
+                .retain(|current, _| !current.eq_ignore_ascii_case(key));
+            request.headers.insert(key.to_string(), value);
+        }
+
+        let mut headers: Vec<(String, String)> = request
+            .headers_with_defaults()
+            .into_iter()
+            .map(|(key, value)| (key.to_lowercase(), canonical_header_value(&value)))
+            .filter(|(key, _)| !UNSIGNED_HEADERS.contains(&key.as_str()))
+            .collect();
+        if !headers.iter().any(|(key, _)| key == "host") {
+            let host = match url.port() {
+                Some(port) => format!("{}:{port}", url.host_str().unwrap_or_default()),
+                None => url.host_str().unwrap_or_default().to_string(),
+            };
+            headers.push(("host".to_string(), host));
+        }
+        headers.sort();
+
+        let signed_headers = headers
+            .iter()
+            .map(|(key, _)| key.as_str())
+            .collect::<Vec<_>>()
+            .join(";");
+        let canonical_request = [
+            request.method.as_str().to_string(),
+            self.canonical_uri(url.path()),
+            canonical_query(&url),
+            headers
+                .iter()
+                .map(|(key, value)| format!("{key}:{value}\n"))
+                .collect(),
+            signed_headers.clone(),
+            payload_hash,
+        ]
+        .join("\n");
+
+        let scope = format!("{date}/{}/{}/aws4_request", self.region, self.service);
+        let string_to_sign = [
+            ALGORITHM,
+            &datetime,
+            &scope,
+            &hex(&Sha256::digest(canonical_request)),
+        ]
+        .join("\n");
+
+        let signing_key = [self.region.as_str(), &self.service, "aws4_request"]
+            .iter()
+            .fold(
+                hmac_sha256(
+                    format!("AWS4{}", self.credentials.secret_access_key).as_bytes(),
+                    date,
+                ),
+                |key, value| hmac_sha256(&key, value),
+            );
+        let signature = hex(&hmac_sha256(&signing_key, &string_to_sign));
+
+        request
+            .headers
+            .retain(|key, _| !key.eq_ignore_ascii_case("authorization"));
+        request.headers.insert(
+            "Authorization".to_string(),
+            format!(
+                "{ALGORITHM} Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+                self.credentials.access_key_id
+            ),
+        );
+
+        Ok(request)
+    }
+
+    /// Path with each segment encoded, twice for every service but S3
+    fn canonical_uri(&self, path: &str) -> String {
+        path.split('/')
+            .map(|segment| {
+                let segment = percent_decode_str(segment).decode_utf8_lossy();
+                match self.service.as_str() {
+                    "s3" => uri_encode(&segment),
+                    _ => uri_encode(&uri_encode(&segment)),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+}
+
+/// Query with its parameters encoded and sorted
+fn canonical_query(url: &url::Url) -> String {
+    let mut params: Vec<(String, String)> = url
+        .query_pairs()
+        .map(|(key, value)| (uri_encode(&key), uri_encode(&value)))
+        .collect();
+    params.sort();
+
+    params
+        .iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn canonical_header_value(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Encodes everything but the unreserved characters of RFC 3986
+fn uri_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (byte as char).to_string()
+            }
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+fn hmac_sha256(key: &[u8], value: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(value.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// Time like '20150830T123600Z', in UTC
+fn amz_datetime(unix_time: u64) -> String {
+    let days = (unix_time / 86_400) as i64;
+    let seconds_of_day = unix_time % 86_400;
+
+    // Days to civil date, by Howard Hinnant's algorithm
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}{month:02}{day:02}T{:02}{:02}{:02}Z",
+        seconds_of_day / 3_600,
+        seconds_of_day % 3_600 / 60,
+        seconds_of_day % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Credentials and time (20150830T123600Z) of the examples of AWS docs
+    const TEST_SUITE_TIME: u64 = 1_440_938_160;
+
+    fn signer(service: &str) -> AwsSigner {
+        AwsSigner {
+            credentials: AwsCredentials {
+                access_key_id: "AKIDEXAMPLE".into(),
+                secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".into(),
+                session_token: None,
+            },
+            region: "us-east-1".into(),
+            service: service.into(),
+        }
+    }
+
+    fn authorization(request: &RequestData) -> &str {
+        request.headers.get("Authorization").unwrap()
+    }
+
+    #[test]
+    fn test_amz_datetime() {
+        assert_eq!("20150830T123600Z", amz_datetime(TEST_SUITE_TIME));
+        assert_eq!("19700101T000000Z", amz_datetime(0));
+        assert_eq!("20240229T235959Z", amz_datetime(1_709_251_199));
+    }
+
+    #[test]
+    fn test_sign_iam_request_of_aws_docs() {
+        let request = RequestData::default()
+            .with_url("https://iam.amazonaws.com/?Action=ListUsers&Version=2010-05-08")
+            .with_headers([(
+                "Content-Type".to_string(),
+                "application/x-www-form-urlencoded; charset=utf-8".to_string(),
+            )]);
+        let request = signer("iam").sign(request, TEST_SUITE_TIME).unwrap();
+
+        assert_eq!(
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/iam/aws4_request, SignedHeaders=content-type;host;x-amz-date, Signature=5d672d79c15b13162d9279b0855cfba6789a8edb4c82c400e06b5924a6f2b5d7",
+            authorization(&request)
+        );
+    }
+
+    #[test]
+    fn test_sign_query_with_sorted_params() {
+        let sign = |url: &str| {
+            let request = RequestData::default().with_url(url);
+            let request = signer("service").sign(request, TEST_SUITE_TIME).unwrap();
+            authorization(&request).to_string()
+        };
+
+        assert_eq!(
+            sign("https://example.amazon.com/?Param1=value1&Param2=value2"),
+            sign("https://example.amazon.com/?Param2=value2&Param1=value1")
+        );
+        assert_ne!(
+            sign("https://example.amazon.com/?Param1=value1"),
+            sign("https://example.amazon.com/?Param1=value2")
+        );
+    }
+
+    #[test]
+    fn test_sign_s3_request_with_payload_hash() {
+        let request = RequestData::default()
+            .with_url("http://localhost:9000/bucket/my file.txt")
+            .with_method(METHODS::PUT)
+            .with_body_payload(BodyPayload::Raw("Hello".into()));
+        let request = signer("s3").sign(request, TEST_SUITE_TIME).unwrap();
+
+        assert_eq!(
+            Some(&"185f8db32271fe25f561a6fc938b2e264306ec304eda518007d1764826381969".to_string()),
+            request.headers.get("X-Amz-Content-Sha256")
+        );
+        assert!(
+            authorization(&request).contains("SignedHeaders=host;x-amz-content-sha256;x-amz-date,")
+        );
+    }
+
+    #[test]
+    fn test_credentials_of_file_profile() {
+        let content = "
+            [default]
+            aws_access_key_id = AKIADEFAULT
+            aws_secret_access_key = default-secret
+
+            # Local MinIO
+            [minio]
+            aws_access_key_id=minioadmin
+            aws_secret_access_key=minio-secret
+        ";
+
+        assert_eq!(
+            Some(AwsCredentials {
+                access_key_id: "minioadmin".into(),
+                secret_access_key: "minio-secret".into(),
+                session_token: None,
+            }),
+            AwsCredentials::from_credentials_file(content, "minio")
+        );
+        assert_eq!(
+            None,
+            AwsCredentials::from_credentials_file(content, "other")
+        );
+    }
+}
@@ -0,0 +1,33 @@
+use tokio::sync::oneshot;
+
+use super::entities::AccessToken;
+use super::service::AuthServiceInstance;
+use crate::app::service_commands::Command;
+
+pub type CommandAuthService<Resp> = Command<AuthServiceInstance, Resp>;
+
+pub struct CommandsFactory;
+
+impl CommandsFactory {
+    pub fn has_profile(profile_name: String) -> CommandAuthService<bool> {
+        let (tx, rx) = oneshot::channel();
+        Command::from(move |service: AuthServiceInstance| {
+            tx.send(service.has_profile(&profile_name)).ok();
+            service
+        })
+        .with_response(rx)
+    }
+
+    pub fn fetch_token(profile_name: String) -> CommandAuthService<anyhow::Result<AccessToken>> {
+        let (tx, rx) = oneshot::channel();
+        Command::from(move |mut service: AuthServiceInstance| {
+            let task = service.fetch_token_async(profile_name);
+            tokio::task::spawn(async move {
+                let token = task.await.map_err(|e| e.into()).and_then(|token| token);
+                tx.send(token).ok();
+            });
+            service
+        })
+        .with_response(rx)
+    }
+}
@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+
+use crate::app::services::request::entities::auth::{Auth, AuthType};
+use crate::app::services::request::entities::methods::METHODS;
+use crate::app::services::request::entities::requests::{BodyPayload, RequestData};
+
+/// Tokens are renewed a bit before expiring, to not expire while the request is sent
+const EXPIRATION_MARGIN_SECS: u64 = 30;
+
+/// Client of an OAuth2 server, getting tokens by the client credentials grant
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OAuth2Profile {
+    pub token_url: String,
+    pub client_id: String,
+    pub client_secret: String,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+}
+
+impl OAuth2Profile {
+    /// Request of a new token to the token endpoint (RFC 6749, section 4.4)
+    pub fn token_request(&self) -> RequestData {
+        let mut fields = vec![("grant_type".to_string(), "client_credentials".to_string())];
+        if !self.scopes.is_empty() {
+            fields.push(("scope".to_string(), self.scopes.join(" ")));
+        }
+
+        RequestData::default()
+            .with_url(&self.token_url)
+            .with_method(METHODS::POST)
+            .with_body_payload(BodyPayload::Form(fields))
+            .with_auth(Auth::init(
+                AuthType::Basic,
+                format!("{}:{}", self.client_id, self.client_secret),
+            ))
+    }
+}
+
+/// Tokens are cached in files of the data directory, named by their profile, so
+/// profile names can not have paths
+pub fn validate_oauth2_profile_name(name: &str) -> anyhow::Result<()> {
+    let is_valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'));
+
+    if !is_valid {
+        return Err(anyhow::Error::msg(format!(
+            "Invalid OAuth2 profile name '{name}', it must only have letters, numbers, '-', '_' and '.', and not start with '.'"
+        )));
+    }
+    Ok(())
+}
+
+/// Token kept in the data directory, to be reused until it expires
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessToken {
+    pub access_token: String,
+    /// Unix time, in seconds. None for tokens which do not expire
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
+}
+
+impl AccessToken {
+    /// Token of the JSON body answered by the token endpoint
+    pub fn from_token_response(body: &str, now: u64) -> anyhow::Result<Self> {
+        #[derive(Deserialize)]
+        struct TokenResponse {
+            access_token: String,
+            expires_in: Option<u64>,
+        }
+
+        let response: TokenResponse = serde_json::from_str(body)?;
+        Ok(Self {
+            access_token: response.access_token,
+            expires_at: response
+                .expires_in
+                .map(|expires_in| now.saturating_add(expires_in)),
+        })
+    }
+
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= now.saturating_add(EXPIRATION_MARGIN_SECS))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_oauth2_profile_names() {
+        assert!(validate_oauth2_profile_name("api.prod_1-eu").is_ok());
+
+        assert!(validate_oauth2_profile_name("").is_err());
+        assert!(validate_oauth2_profile_name("..").is_err());
+        assert!(validate_oauth2_profile_name("../config").is_err());
+        assert!(validate_oauth2_profile_name("dir/api").is_err());
+    }
+
+    #[test]
+    fn test_token_request_of_profile() {
+        let profile = OAuth2Profile {
+            token_url: "https://auth.example.com/token".into(),
+            client_id: "app".into(),
+            client_secret: "secret".into(),
+            scopes: vec!["read".into(), "write".into()],
+        };
+        let request = profile.token_request();
+
+        assert_eq!(METHODS::POST, request.method);
+        assert_eq!(
+            Some(BodyPayload::Form(vec![
+                ("grant_type".into(), "client_credentials".into()),
+                ("scope".into(), "read write".into()),
+            ])),
+            request.body
+        );
+        assert_eq!(
+            Some(Auth::init(AuthType::Basic, "app:secret")),
+            request.auth
+        );
+    }
+
+    #[test]
+    fn test_expiration_of_token_response() {
+        let token = AccessToken::from_token_response(
+            r#"{ "access_token": "abc", "token_type": "Bearer", "expires_in": 3600 }"#,
+            1_000,
+        )
+        .unwrap();
+
+        assert_eq!("abc", token.access_token);
+        assert_eq!(Some(4_600), token.expires_at);
+        assert!(!token.is_expired(4_000));
+        assert!(token.is_expired(4_580));
+
+        let token =
+            AccessToken::from_token_response(r#"{ "access_token": "abc" }"#, 1_000).unwrap();
+        assert!(!token.is_expired(u64::MAX));
+        assert!(AccessToken::from_token_response(r#"{ "error": "invalid_client" }"#, 0).is_err());
+    }
+}
@@ -0,0 +1,14 @@
+use tokio::task::JoinHandle;
+
+use super::entities::AccessToken;
+
+pub trait AuthServiceFacade: Send {
+    /// Whether the OAuth2 profile is defined at config
+    fn has_profile(&self, profile_name: &str) -> bool;
+
+    /// New access token of the OAuth2 profile, asked to its token endpoint
+    fn fetch_token_async(
+        &mut self,
+        profile_name: String,
+    ) -> JoinHandle<anyhow::Result<AccessToken>>;
+}
@@ -0,0 +1,5 @@
+pub mod aws_sigv4;
+pub mod commands;
+pub mod entities;
+pub mod facade;
+pub mod service;
@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+
+use anyhow::Error;
+use tokio::task::JoinHandle;
+
+use super::entities::{AccessToken, OAuth2Profile};
+use super::facade::AuthServiceFacade;
+use crate::app::services::web_client::repository_client::HttpClientRepository;
+use crate::utils::time::unix_now;
+
+pub type AuthServiceInstance = Box<dyn AuthServiceFacade>;
+
+pub struct AuthService {
+    pub profiles: HashMap<String, OAuth2Profile>,
+    pub http_client: Box<dyn HttpClientRepository>,
+}
+
+impl AuthService {
+    pub fn init(
+        profiles: HashMap<String, OAuth2Profile>,
+        repository: impl HttpClientRepository + 'static,
+    ) -> Self {
+        Self {
+            profiles,
+            http_client: Box::new(repository),
+        }
+    }
+}
+
+impl AuthServiceFacade for AuthService {
+    fn has_profile(&self, profile_name: &str) -> bool {
+        self.profiles.contains_key(profile_name)
+    }
+
+    fn fetch_token_async(
+        &mut self,
+        profile_name: String,
+    ) -> JoinHandle<anyhow::Result<AccessToken>> {
+        let Some(profile) = self.profiles.get(&profile_name) else {
+            return tokio::task::spawn(async move {
+                Err(Error::msg(format!(
+                    "OAuth2 profile '{profile_name}' is not defined at config file"
+                )))
+            });
+        };
+
+        let task = self.http_client.submit_request(profile.token_request());
+        tokio::task::spawn(async move {
+            let response = task.await??;
+            if !(200..300).contains(&response.status) {
+                return Err(Error::msg(format!(
+                    "Token endpoint of OAuth2 profile '{profile_name}' answered with status {}: {}",
+                    response.status, response.body
+                )));
+            }
+
+            AccessToken::from_token_response(&response.body, unix_now()).map_err(|err| {
+                Error::msg(format!(
+                    "Invalid token response of OAuth2 profile '{profile_name}': {err}"
+                ))
+            })
+        })
+    }
+}
@@ -6,7 +6,9 @@ use tokio::sync::oneshot;
 use super::service::FileServiceInstance;
 use crate::app::service_commands::Command;
 
+pub mod oauth2_tokens;
 pub mod requests;
+pub mod sessions;
 
 pub type CommandFileService<Resp> = Command<FileServiceInstance, Resp>;
 
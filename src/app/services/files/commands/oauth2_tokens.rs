@@ -0,0 +1,28 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use tokio::sync::oneshot;
+
+use super::{CommandFileService, CommandsFactory};
+use crate::app::service_commands::Command;
+use crate::app::services::auth::entities::validate_oauth2_profile_name;
+use crate::app::services::files::service::FileServiceInstance;
+
+const OAUTH2_TOKENS_FOLDER: &str = "oauth2_tokens/";
+
+impl CommandsFactory {
+    pub fn get_or_create_file_of_oauth2_token(
+        profile_name: String,
+    ) -> CommandFileService<Result<PathBuf>> {
+        let (tx, rx) = oneshot::channel();
+
+        Command::from(move |service: FileServiceInstance| {
+            let resp = validate_oauth2_profile_name(&profile_name).and_then(|_| {
+                service.get_or_create_data_file(format!("{OAUTH2_TOKENS_FOLDER}{profile_name}"))
+            });
+            tx.send(resp).ok();
+            service
+        })
+        .with_response(rx)
+    }
+}
@@ -0,0 +1,28 @@
+use std::path::PathBuf;
+
+use anyhow::Result;
+use tokio::sync::oneshot;
+
+use super::{CommandFileService, CommandsFactory};
+use crate::app::service_commands::Command;
+use crate::app::services::files::service::FileServiceInstance;
+use crate::app::services::request::entities::sessions::validate_session_name;
+
+const SESSIONS_FOLDER: &str = "sessions/";
+
+impl CommandsFactory {
+    pub fn get_or_create_file_of_session(
+        session_name: String,
+    ) -> CommandFileService<Result<PathBuf>> {
+        let (tx, rx) = oneshot::channel();
+
+        Command::from(move |service: FileServiceInstance| {
+            let resp = validate_session_name(&session_name).and_then(|_| {
+                service.get_or_create_data_file(format!("{SESSIONS_FOLDER}{session_name}"))
+            });
+            tx.send(resp).ok();
+            service
+        })
+        .with_response(rx)
+    }
+}
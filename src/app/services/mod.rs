@@ -1,3 +1,4 @@
+pub mod auth;
 pub mod files;
 pub mod request;
 pub mod web_client;
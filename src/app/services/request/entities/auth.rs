@@ -0,0 +1,264 @@
+use std::str::FromStr;
+
+use anyhow::Error;
+use base64::Engine;
+use md5::Md5;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Authentication of a request, kept apart from its headers
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Auth {
+    #[serde(rename = "type", default)]
+    pub auth_type: AuthType,
+    /// 'user:password' for basic and digest, the token for bearer, the name of profile
+    /// for oauth2, or 'region:service[:profile]' for aws-sigv4
+    pub credentials: String,
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthType {
+    #[default]
+    Basic,
+    Bearer,
+    Digest,
+    /// Bearer token got from the token endpoint of an OAuth2 profile
+    OAuth2,
+    /// AWS Signature Version 4, signed with keys of environment or of a profile
+    #[serde(rename = "aws-sigv4")]
+    AwsSigV4,
+}
+
+impl Auth {
+    pub fn init(auth_type: AuthType, credentials: impl Into<String>) -> Self {
+        Self {
+            auth_type,
+            credentials: credentials.into(),
+        }
+    }
+
+    pub fn username(&self) -> &str {
+        self.credentials
+            .split_once(':')
+            .map_or(self.credentials.as_str(), |(username, _)| username)
+    }
+
+    pub fn password(&self) -> &str {
+        self.credentials
+            .split_once(':')
+            .map_or("", |(_, password)| password)
+    }
+
+    /// Value of 'Authorization' header sent with the request. Digest has none, it only
+    /// answers to the challenge of server, OAuth2 is sent with its token as bearer and
+    /// AWS signature is added once the request is final
+    pub fn header_value(&self) -> Option<String> {
+        match self.auth_type {
+            AuthType::Basic => {
+                let credentials = format!("{}:{}", self.username(), self.password());
+                Some(format!(
+                    "Basic {}",
+                    base64::engine::general_purpose::STANDARD.encode(credentials)
+                ))
+            }
+            AuthType::Bearer => Some(format!("Bearer {}", self.credentials)),
+            AuthType::Digest | AuthType::OAuth2 | AuthType::AwsSigV4 => None,
+        }
+    }
+
+    /// Value of 'Authorization' header answering to a 'WWW-Authenticate: Digest ...'
+    /// challenge (RFC 7616), for the request with the given method and URI
+    pub fn digest_answer(
+        &self,
+        challenge: &str,
+        method: &str,
+        uri: &str,
+        cnonce: &str,
+    ) -> anyhow::Result<String> {
+        let params = challenge_params(challenge);
+        let param = |key: &str| {
+            params
+                .iter()
+                .find(|(param_key, _)| param_key.eq_ignore_ascii_case(key))
+                .map(|(_, value)| value.as_str())
+        };
+
+        let realm = param("realm").unwrap_or_default();
+        let nonce = param("nonce").ok_or(Error::msg("Digest challenge without nonce"))?;
+        let algorithm = param("algorithm").unwrap_or("MD5");
+        let qop = param("qop").map(|qop| {
+            qop.split(',')
+                .map(str::trim)
+                .find(|qop| *qop == "auth")
+                .unwrap_or("auth")
+        });
+        let nc = "00000001";
+
+        let hash: fn(&str) -> String = match algorithm.to_uppercase().as_str() {
+            "MD5" | "MD5-SESS" => |value| hex(&Md5::digest(value)),
+            "SHA-256" | "SHA-256-SESS" => |value| hex(&Sha256::digest(value)),
+            _ => {
+                return Err(Error::msg(format!(
+                    "Digest algorithm '{algorithm}' is not supported"
+                )))
+            }
+        };
+
+        let mut ha1 = hash(&format!("{}:{realm}:{}", self.username(), self.password()));
+        if algorithm.to_lowercase().ends_with("-sess") {
+            ha1 = hash(&format!("{ha1}:{nonce}:{cnonce}"));
+        }
+        let ha2 = hash(&format!("{method}:{uri}"));
+        let response = match qop {
+            Some(qop) => hash(&format!("{ha1}:{nonce}:{nc}:{cnonce}:{qop}:{ha2}")),
+            None => hash(&format!("{ha1}:{nonce}:{ha2}")),
+        };
+
+        let mut header = format!(
+            r#"Digest username="{}", realm="{realm}", nonce="{nonce}", uri="{uri}", algorithm={algorithm}, response="{response}""#,
+            self.username()
+        );
+        if let Some(qop) = qop {
+            header.push_str(&format!(r#", qop={qop}, nc={nc}, cnonce="{cnonce}""#));
+        }
+        if let Some(opaque) = param("opaque") {
+            header.push_str(&format!(r#", opaque="{opaque}""#));
+        }
+
+        Ok(header)
+    }
+}
+
+impl AuthType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuthType::Basic => "basic",
+            AuthType::Bearer => "bearer",
+            AuthType::Digest => "digest",
+            AuthType::OAuth2 => "oauth2",
+            AuthType::AwsSigV4 => "aws-sigv4",
+        }
+    }
+}
+
+impl FromStr for AuthType {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "basic" => Ok(AuthType::Basic),
+            "bearer" => Ok(AuthType::Bearer),
+            "digest" => Ok(AuthType::Digest),
+            "oauth2" => Ok(AuthType::OAuth2),
+            "aws-sigv4" => Ok(AuthType::AwsSigV4),
+            _ => Err(Error::msg(format!(
+                "Invalid auth type '{s}', it must be 'basic', 'bearer', 'digest', 'oauth2' or 'aws-sigv4'"
+            ))),
+        }
+    }
+}
+
+/// Parameters of a challenge like 'Digest realm="api", qop="auth,auth-int"'
+fn challenge_params(challenge: &str) -> Vec<(String, String)> {
+    let params = challenge
+        .trim()
+        .split_once(char::is_whitespace)
+        .map_or("", |(_, params)| params);
+
+    let mut result = Vec::new();
+    let mut chars = params.chars().peekable();
+
+    loop {
+        let key: String = chars
+            .by_ref()
+            .skip_while(|c| c.is_whitespace() || *c == ',')
+            .take_while(|c| *c != '=')
+            .collect();
+        if key.is_empty() {
+            break;
+        }
+
+        let mut value = String::new();
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            while let Some(c) = chars.next() {
+                match c {
+                    '\\' => value.extend(chars.next()),
+                    '"' => break,
+                    c => value.push(c),
+                }
+            }
+        } else {
+            value = chars.by_ref().take_while(|c| *c != ',').collect();
+        }
+
+        result.push((key.trim().to_string(), value.trim().to_string()));
+    }
+
+    result
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_of_basic_and_bearer_auth() {
+        assert_eq!(
+            Some("Basic dXNlcjpwYXNz".to_string()),
+            Auth::init(AuthType::Basic, "user:pass").header_value()
+        );
+        assert_eq!(
+            Some("Basic dXNlcjo=".to_string()),
+            Auth::init(AuthType::Basic, "user").header_value()
+        );
+        assert_eq!(
+            Some("Bearer abc.def".to_string()),
+            Auth::init(AuthType::Bearer, "abc.def").header_value()
+        );
+        assert_eq!(
+            None,
+            Auth::init(AuthType::Digest, "user:pass").header_value()
+        );
+    }
+
+    #[test]
+    fn test_answer_digest_challenge_of_rfc_2617() {
+        let auth = Auth::init(AuthType::Digest, "Mufasa:Circle Of Life");
+        let challenge = r#"Digest realm="testrealm@host.com", qop="auth,auth-int", nonce="dcd98b7102dd2f0e8b11d0f600bfb0c093", opaque="5ccc069c403ebaf9f0171e9517f40e41""#;
+
+        let answer = auth
+            .digest_answer(challenge, "GET", "/dir/index.html", "0a4f113b")
+            .unwrap();
+
+        assert!(answer.starts_with(r#"Digest username="Mufasa", realm="testrealm@host.com""#));
+        assert!(answer.contains(r#"response="6629fae49393a05397450978507c4ef1""#));
+        assert!(answer.contains(r#"qop=auth, nc=00000001, cnonce="0a4f113b""#));
+        assert!(answer.contains(r#"opaque="5ccc069c403ebaf9f0171e9517f40e41""#));
+    }
+
+    #[test]
+    fn test_answer_digest_challenge_with_sha_256() {
+        // Example of RFC 7616, section 3.9.1
+        let auth = Auth::init(AuthType::Digest, "Mufasa:Circle of Life");
+        let challenge = r#"Digest realm="http-auth@example.org", qop="auth, auth-int", algorithm=SHA-256, nonce="7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v", opaque="FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS""#;
+
+        let answer = auth
+            .digest_answer(
+                challenge,
+                "GET",
+                "/dir/index.html",
+                "f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ",
+            )
+            .unwrap();
+
+        assert!(answer.contains(
+            r#"response="753927fa0e85d155564e2e272a28d1802ca10daf4496794697cf8db5856cb6c1""#
+        ));
+    }
+}
@@ -0,0 +1,186 @@
+use anyhow::{Error, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::multipart::{merge_multipart_fields, MultipartField};
+use super::requests::{merge_form_fields, BodyPayload};
+
+/// A body value read from a file when the request is submitted ('=@', ':=@' and '--raw @')
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BodyFileReference {
+    /// Nested keys of the field to set, or empty to use the file as the whole body
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keys: Vec<String>,
+    pub path: String,
+    /// Parse the file content as JSON, instead of using it as a string
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub as_json: bool,
+}
+
+impl BodyFileReference {
+    pub fn is_whole_body(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Name of the field in forms, like 'user[name]'
+    fn field_name(&self) -> String {
+        let mut keys = self.keys.iter();
+        let root_key = keys.next().cloned().unwrap_or_default();
+        keys.fold(root_key, |name, key| format!("{name}[{key}]"))
+    }
+
+    fn read(&self) -> Result<String> {
+        std::fs::read_to_string(&self.path).map_err(|err| self.read_error(err))
+    }
+
+    async fn read_async(&self) -> Result<String> {
+        tokio::fs::read_to_string(&self.path)
+            .await
+            .map_err(|err| self.read_error(err))
+    }
+
+    fn read_error(&self, err: std::io::Error) -> Error {
+        Error::msg(format!("Could not read file '{}': {err}", self.path))
+    }
+
+    fn parse_value(&self, content: String) -> Result<Value> {
+        if !self.as_json {
+            return Ok(Value::String(content));
+        }
+
+        serde_json::from_str(&content).map_err(|err| {
+            Error::msg(format!(
+                "File '{}' does not have a valid JSON: {err}",
+                self.path
+            ))
+        })
+    }
+}
+
+/// Replaces references to the same keys and appends the new ones. A reference to the
+/// whole body discards all the previous ones
+pub fn merge_body_file_references(
+    references: &mut Vec<BodyFileReference>,
+    other_references: Vec<BodyFileReference>,
+) {
+    for other_reference in other_references {
+        if other_reference.is_whole_body() {
+            references.clear();
+        }
+        references.retain(|reference| reference.keys != other_reference.keys);
+        references.push(other_reference);
+    }
+}
+
+/// Reads the content of every referenced file, setting it in body
+pub fn resolve_body_files(
+    body: Option<BodyPayload>,
+    references: &[BodyFileReference],
+) -> Result<Option<BodyPayload>> {
+    let contents = references
+        .iter()
+        .map(BodyFileReference::read)
+        .collect::<Result<Vec<_>>>()?;
+    set_body_files(body, references, contents)
+}
+
+/// Same as `resolve_body_files`, without blocking the async runtime while reading
+pub async fn resolve_body_files_async(
+    body: Option<BodyPayload>,
+    references: &[BodyFileReference],
+) -> Result<Option<BodyPayload>> {
+    let mut contents = Vec::with_capacity(references.len());
+    for reference in references {
+        contents.push(reference.read_async().await?);
+    }
+    set_body_files(body, references, contents)
+}
+
+/// Sets the content read of each referenced file in body
+fn set_body_files(
+    mut body: Option<BodyPayload>,
+    references: &[BodyFileReference],
+    contents: Vec<String>,
+) -> Result<Option<BodyPayload>> {
+    for (reference, content) in references.iter().zip(contents) {
+        if reference.is_whole_body() {
+            // Fields already given are kept over the ones of file. A form body reads
+            // the file as form fields, instead of as JSON or raw text
+            body = match (body, content) {
+                (Some(BodyPayload::Form(current_fields)), content) => {
+                    let mut fields: Vec<(String, String)> =
+                        serde_urlencoded::from_str(content.trim_end()).map_err(|err| {
+                            Error::msg(format!(
+                                "File '{}' does not have valid form fields: {err}",
+                                reference.path
+                            ))
+                        })?;
+                    merge_form_fields(&mut fields, current_fields);
+                    Some(BodyPayload::Form(fields))
+                }
+                (body, content) => match (BodyPayload::from_str(&content), body) {
+                    (
+                        BodyPayload::Json(Value::Object(mut file_map_json)),
+                        Some(BodyPayload::Json(Value::Object(current_map_json))),
+                    ) => {
+                        file_map_json.extend(current_map_json);
+                        Some(BodyPayload::Json(Value::Object(file_map_json)))
+                    }
+                    (file_body, _) => Some(file_body),
+                },
+            };
+            continue;
+        }
+
+        let value = reference.parse_value(content)?;
+        let field_name = reference.field_name();
+        let as_text = || match &value {
+            Value::String(value) => value.clone(),
+            value => value.to_string(),
+        };
+
+        body = Some(match body {
+            Some(BodyPayload::Form(mut fields)) => {
+                fields.retain(|(key, _)| *key != field_name);
+                fields.push((field_name, as_text()));
+                BodyPayload::Form(fields)
+            }
+            Some(BodyPayload::Multipart(mut fields)) => {
+                let text = as_text();
+                merge_multipart_fields(
+                    &mut fields,
+                    vec![MultipartField::Text {
+                        name: field_name,
+                        value: text,
+                    }],
+                );
+                BodyPayload::Multipart(fields)
+            }
+            Some(BodyPayload::Json(mut root_value)) => {
+                set_nested_value(&mut root_value, &reference.keys, value);
+                BodyPayload::Json(root_value)
+            }
+            _ => {
+                let mut root_value = Value::Object(Map::new());
+                set_nested_value(&mut root_value, &reference.keys, value);
+                BodyPayload::Json(root_value)
+            }
+        });
+    }
+
+    Ok(body)
+}
+
+fn set_nested_value(root_value: &mut Value, keys: &[String], value: Value) {
+    let target_value = keys.iter().fold(root_value, |current_value, key| {
+        if !current_value.is_object() {
+            *current_value = Value::Object(Map::new());
+        }
+        match current_value {
+            Value::Object(map) => map.entry(key).or_insert(Value::Null),
+            _ => unreachable!(),
+        }
+    });
+
+    *target_value = value;
+}
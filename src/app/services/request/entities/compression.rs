@@ -0,0 +1,85 @@
+use std::io::Write;
+use std::str::FromStr;
+
+use flate2::write::{GzEncoder, ZlibEncoder};
+use serde::{Deserialize, Serialize};
+
+/// Compression applied to the body of a request, informed to server by 'Content-Encoding'.
+/// Its name is also the value of that header
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BodyCompression {
+    #[default]
+    Deflate,
+    Gzip,
+}
+
+impl BodyCompression {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BodyCompression::Deflate => "deflate",
+            BodyCompression::Gzip => "gzip",
+        }
+    }
+
+    /// Deflate is sent in the zlib format, as defined for HTTP by RFC 9110
+    pub fn compress(&self, body: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let level = flate2::Compression::default();
+        let compressed = match self {
+            BodyCompression::Deflate => {
+                let mut encoder = ZlibEncoder::new(Vec::new(), level);
+                encoder.write_all(body)?;
+                encoder.finish()?
+            }
+            BodyCompression::Gzip => {
+                let mut encoder = GzEncoder::new(Vec::new(), level);
+                encoder.write_all(body)?;
+                encoder.finish()?
+            }
+        };
+        Ok(compressed)
+    }
+}
+
+impl FromStr for BodyCompression {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "deflate" => Ok(BodyCompression::Deflate),
+            "gzip" => Ok(BodyCompression::Gzip),
+            _ => Err(anyhow::Error::msg(format!(
+                "Invalid compression '{s}', it must be 'deflate' or 'gzip'"
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use flate2::read::{GzDecoder, ZlibDecoder};
+
+    use super::*;
+
+    #[test]
+    fn test_compress_body() {
+        let body = r#"{"name":"treq","name":"treq","name":"treq"}"#.as_bytes();
+
+        let mut decoded = String::new();
+        let deflated = BodyCompression::Deflate.compress(body).unwrap();
+        ZlibDecoder::new(deflated.as_slice())
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(body, decoded.as_bytes());
+
+        let mut decoded = String::new();
+        let gzipped = BodyCompression::Gzip.compress(body).unwrap();
+        assert_eq!([0x1f, 0x8b], gzipped[..2]);
+        GzDecoder::new(gzipped.as_slice())
+            .read_to_string(&mut decoded)
+            .unwrap();
+        assert_eq!(body, decoded.as_bytes());
+    }
+}
@@ -0,0 +1,226 @@
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::time::unix_now;
+
+/// Cookies received by 'Set-Cookie' headers, sent back to the URLs they match
+#[derive(Default, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CookieJar {
+    #[serde(default)]
+    pub cookies: Vec<Cookie>,
+}
+
+#[derive(Default, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cookie {
+    pub name: String,
+    pub value: String,
+    pub domain: String,
+    /// Only sent to exactly `domain`, not to its subdomains
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub host_only: bool,
+    pub path: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub secure: bool,
+    /// Unix time, in seconds. None for cookies of a single browser session, which
+    /// here are kept until the server removes them
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires: Option<u64>,
+}
+
+impl CookieJar {
+    pub fn is_empty(&self) -> bool {
+        self.cookies.is_empty()
+    }
+
+    /// Value of 'Cookie' header to send to the URL, with more specific paths first
+    pub fn header_for(&self, url: &url::Url) -> Option<String> {
+        let host = url.host_str()?.to_lowercase();
+        let now = unix_now();
+
+        let mut cookies: Vec<&Cookie> = self
+            .cookies
+            .iter()
+            .filter(|cookie| !cookie.is_expired(now))
+            .filter(|cookie| !cookie.secure || url.scheme() == "https")
+            .filter(|cookie| match cookie.host_only {
+                true => host == cookie.domain,
+                false => domain_matches(&host, &cookie.domain),
+            })
+            .filter(|cookie| path_matches(url.path(), &cookie.path))
+            .collect();
+        cookies.sort_by_key(|cookie| std::cmp::Reverse(cookie.path.len()));
+
+        let header = cookies
+            .iter()
+            .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+            .collect::<Vec<_>>()
+            .join("; ");
+
+        (!header.is_empty()).then_some(header)
+    }
+
+    /// Stores the cookies of 'Set-Cookie' headers received from the URL. Expired ones
+    /// remove the cookie, and the ones of other domains are ignored
+    pub fn store_from_headers<'a>(
+        &mut self,
+        url: &url::Url,
+        set_cookie_headers: impl IntoIterator<Item = &'a str>,
+    ) {
+        let now = unix_now();
+
+        for header in set_cookie_headers {
+            let Some(cookie) = Cookie::parse(header, url, now) else {
+                continue;
+            };
+
+            // A replaced cookie keeps its position, as it keeps the creation time in browsers
+            let current = self.cookies.iter_mut().find(|current| {
+                current.name == cookie.name
+                    && current.domain == cookie.domain
+                    && current.path == cookie.path
+            });
+            match current {
+                Some(current) => *current = cookie,
+                None => self.cookies.push(cookie),
+            }
+        }
+
+        self.cookies.retain(|cookie| !cookie.is_expired(now));
+    }
+}
+
+impl Cookie {
+    fn parse(header: &str, url: &url::Url, now: u64) -> Option<Cookie> {
+        let host = url.host_str()?.to_lowercase();
+        let mut parts = header.split(';');
+        let (name, value) = parts.next()?.split_once('=')?;
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+
+        let mut cookie = Cookie {
+            name: name.to_string(),
+            value: value.trim().trim_matches('"').to_string(),
+            domain: host.clone(),
+            host_only: true,
+            path: default_path(url.path()),
+            ..Default::default()
+        };
+        let mut max_age = None;
+
+        for attribute in parts {
+            let (key, value) = attribute.split_once('=').unwrap_or((attribute, ""));
+            let value = value.trim();
+
+            match key.trim().to_lowercase().as_str() {
+                "domain" if !value.is_empty() => {
+                    let domain = value.trim_start_matches('.').to_lowercase();
+                    if !domain_matches(&host, &domain) {
+                        return None;
+                    }
+                    cookie.domain = domain;
+                    cookie.host_only = false;
+                }
+                "path" if value.starts_with('/') => cookie.path = value.to_string(),
+                "secure" => cookie.secure = true,
+                "max-age" => max_age = value.parse::<i64>().ok(),
+                "expires" => {
+                    cookie.expires = httpdate::parse_http_date(value)
+                        .ok()
+                        .map(|date| date.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()))
+                }
+                _ => {}
+            }
+        }
+
+        // 'Max-Age' has priority over 'Expires'
+        if let Some(max_age) = max_age {
+            cookie.expires = Some(now.saturating_add_signed(max_age.max(-1)));
+        }
+
+        Some(cookie)
+    }
+
+    fn is_expired(&self, now: u64) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+}
+
+fn domain_matches(host: &str, domain: &str) -> bool {
+    host == domain || host.ends_with(&format!(".{domain}"))
+}
+
+fn path_matches(request_path: &str, cookie_path: &str) -> bool {
+    request_path == cookie_path
+        || (request_path.starts_with(cookie_path)
+            && (cookie_path.ends_with('/') || request_path[cookie_path.len()..].starts_with('/')))
+}
+
+/// Directory of the request path, used when the cookie does not define a 'Path'
+fn default_path(request_path: &str) -> String {
+    match request_path.rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(index) => request_path[..index].to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(value: &str) -> url::Url {
+        url::Url::parse(value).unwrap()
+    }
+
+    #[test]
+    fn test_send_cookies_only_to_matching_urls() {
+        let mut jar = CookieJar::default();
+        jar.store_from_headers(
+            &url("https://app.example.com/auth/login"),
+            [
+                "session=abc; Path=/; HttpOnly",
+                "theme=dark; Domain=example.com; Path=/",
+                "token=xyz; Secure",
+                "tracker=1; Domain=other.com",
+            ],
+        );
+
+        assert_eq!(
+            Some("token=xyz; session=abc; theme=dark".to_string()),
+            jar.header_for(&url("https://app.example.com/auth/me"))
+        );
+        assert_eq!(
+            Some("session=abc; theme=dark".to_string()),
+            jar.header_for(&url("http://app.example.com/users"))
+        );
+        assert_eq!(
+            Some("theme=dark".to_string()),
+            jar.header_for(&url("http://api.example.com/"))
+        );
+        assert_eq!(None, jar.header_for(&url("https://other.com/")));
+    }
+
+    #[test]
+    fn test_replace_and_remove_cookies() {
+        let mut jar = CookieJar::default();
+        let login_url = url("http://localhost/login");
+
+        jar.store_from_headers(&login_url, ["session=abc; Path=/", "lang=en; Max-Age=60"]);
+        jar.store_from_headers(&login_url, ["session=def; Path=/"]);
+        assert_eq!(
+            Some("session=def; lang=en".to_string()),
+            jar.header_for(&url("http://localhost/"))
+        );
+
+        jar.store_from_headers(
+            &login_url,
+            [
+                "session=; Path=/; Expires=Thu, 01 Jan 1970 00:00:00 GMT",
+                "lang=; Max-Age=0",
+            ],
+        );
+        assert!(jar.is_empty());
+    }
+}
@@ -0,0 +1,270 @@
+use std::fmt::Display;
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// How the host of URL is resolved to the address connected to. Rules of `resolve`
+/// replace the lookup of their host and port, like '--resolve' of curl, and
+/// `ip_version` only keeps the addresses of that family
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct DnsSettings {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub resolve: Vec<ResolveRule>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ip_version: Option<IpVersion>,
+}
+
+impl DnsSettings {
+    /// Adds a rule like 'HOST:PORT:ADDR', replacing the one of same host and port
+    pub fn with_rule(mut self, rule: &str) -> anyhow::Result<Self> {
+        let rule = ResolveRule::from_str(rule)?;
+        self.resolve
+            .retain(|current| !current.is_for(&rule.host, rule.port));
+        self.resolve.push(rule);
+        Ok(self)
+    }
+
+    pub fn with_ip_version(mut self, value: IpVersion) -> Self {
+        self.ip_version = Some(value);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == DnsSettings::default()
+    }
+
+    pub fn be_overwrite_by(self, other: DnsSettings) -> DnsSettings {
+        let mut resolve: Vec<ResolveRule> = self
+            .resolve
+            .into_iter()
+            .filter(|rule| {
+                !other
+                    .resolve
+                    .iter()
+                    .any(|o| o.is_for(&rule.host, rule.port))
+            })
+            .collect();
+        resolve.extend(other.resolve);
+
+        DnsSettings {
+            resolve,
+            ip_version: other.ip_version.or(self.ip_version),
+        }
+    }
+
+    /// Addresses to connect to for the host and port, instead of looking them up.
+    /// Fails when the rule has no address of the IP version to use
+    pub fn addrs_for(&self, host: &str, port: u16) -> Option<anyhow::Result<Vec<SocketAddr>>> {
+        let rule = self.resolve.iter().find(|rule| rule.is_for(host, port))?;
+        let addrs: Vec<SocketAddr> = rule
+            .addrs
+            .iter()
+            .filter(|addr| self.accepts(addr))
+            .map(|addr| SocketAddr::new(*addr, port))
+            .collect();
+
+        match (addrs.is_empty(), self.ip_version) {
+            (true, Some(ip_version)) => Some(Err(anyhow::Error::msg(format!(
+                "No {ip_version} address in resolve rule '{rule}'"
+            )))),
+            _ => Some(Ok(addrs)),
+        }
+    }
+
+    /// Whether the address is of the IP version to use, if any
+    pub fn accepts(&self, addr: &IpAddr) -> bool {
+        match self.ip_version {
+            Some(IpVersion::V4) => addr.is_ipv4(),
+            Some(IpVersion::V6) => addr.is_ipv6(),
+            None => true,
+        }
+    }
+}
+
+/// Addresses of a host and port, saved like 'api.example.com:443:10.0.0.5'.
+/// Many addresses are separated by comma, and IPv6 ones may be given in brackets
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct ResolveRule {
+    pub host: String,
+    pub port: u16,
+    pub addrs: Vec<IpAddr>,
+}
+
+impl ResolveRule {
+    pub fn is_for(&self, host: &str, port: u16) -> bool {
+        self.port == port && self.host.eq_ignore_ascii_case(host)
+    }
+}
+
+impl FromStr for ResolveRule {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid_rule = || {
+            anyhow::Error::msg(format!(
+                "Invalid resolve '{s}', it must be like 'HOST:PORT:ADDRESS', as 'example.com:443:10.0.0.5'"
+            ))
+        };
+
+        let mut parts = s.splitn(3, ':');
+        let (Some(host), Some(port), Some(addrs)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid_rule());
+        };
+        let port = port.parse::<u16>().map_err(|_| invalid_rule())?;
+        let addrs = addrs
+            .split(',')
+            .map(|addr| {
+                let addr = addr.trim().trim_start_matches('[').trim_end_matches(']');
+                IpAddr::from_str(addr).map_err(|_| invalid_rule())
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        if host.is_empty() {
+            return Err(invalid_rule());
+        }
+
+        Ok(ResolveRule {
+            host: host.to_string(),
+            port,
+            addrs,
+        })
+    }
+}
+
+impl Display for ResolveRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let addrs: Vec<String> = self
+            .addrs
+            .iter()
+            .map(|addr| match addr {
+                IpAddr::V4(addr) => addr.to_string(),
+                IpAddr::V6(addr) => format!("[{addr}]"),
+            })
+            .collect();
+        write!(f, "{}:{}:{}", self.host, self.port, addrs.join(","))
+    }
+}
+
+impl TryFrom<String> for ResolveRule {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        ResolveRule::from_str(&value)
+    }
+}
+
+impl From<ResolveRule> for String {
+    fn from(value: ResolveRule) -> Self {
+        value.to_string()
+    }
+}
+
+/// Family of the IP addresses to connect to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IpVersion {
+    V4,
+    V6,
+}
+
+impl Display for IpVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IpVersion::V4 => write!(f, "IPv4"),
+            IpVersion::V6 => write!(f, "IPv6"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_resolve_rules() {
+        let rule = ResolveRule::from_str("api.example.com:443:10.0.0.5").unwrap();
+        assert_eq!(rule.host, "api.example.com");
+        assert_eq!(rule.port, 443);
+        assert_eq!(rule.addrs, vec![IpAddr::from([10, 0, 0, 5])]);
+        assert_eq!(rule.to_string(), "api.example.com:443:10.0.0.5");
+
+        let rule = ResolveRule::from_str("api.example.com:443:[::1],10.0.0.5").unwrap();
+        assert_eq!(
+            rule.addrs,
+            vec![
+                IpAddr::from_str("::1").unwrap(),
+                IpAddr::from([10, 0, 0, 5])
+            ]
+        );
+        assert_eq!(rule.to_string(), "api.example.com:443:[::1],10.0.0.5");
+
+        assert!(ResolveRule::from_str("api.example.com:10.0.0.5").is_err());
+        assert!(ResolveRule::from_str("api.example.com:https:10.0.0.5").is_err());
+        assert!(ResolveRule::from_str(":443:10.0.0.5").is_err());
+    }
+
+    #[test]
+    fn test_addresses_of_host_and_port() {
+        let dns = DnsSettings::default()
+            .with_rule("api.example.com:443:10.0.0.5,::1")
+            .unwrap();
+
+        assert_eq!(
+            dns.addrs_for("API.example.com", 443).unwrap().unwrap(),
+            vec![
+                SocketAddr::from(([10, 0, 0, 5], 443)),
+                SocketAddr::from_str("[::1]:443").unwrap(),
+            ]
+        );
+        assert!(dns.addrs_for("api.example.com", 80).is_none());
+
+        let dns = dns.with_ip_version(IpVersion::V6);
+        assert_eq!(
+            dns.addrs_for("api.example.com", 443).unwrap().unwrap(),
+            vec![SocketAddr::from_str("[::1]:443").unwrap()]
+        );
+    }
+
+    #[test]
+    fn test_error_when_rule_has_no_address_of_ip_version() {
+        let dns = DnsSettings::default()
+            .with_rule("api.example.com:443:10.0.0.5")
+            .unwrap()
+            .with_ip_version(IpVersion::V6);
+
+        let err = dns.addrs_for("api.example.com", 443).unwrap().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "No IPv6 address in resolve rule 'api.example.com:443:10.0.0.5'"
+        );
+    }
+
+    #[test]
+    fn test_overwrite_rules_of_same_host_and_port() {
+        let saved = DnsSettings::default()
+            .with_rule("api.example.com:443:10.0.0.5")
+            .unwrap()
+            .with_rule("api.example.com:80:10.0.0.5")
+            .unwrap();
+        let given = DnsSettings::default()
+            .with_rule("api.example.com:443:10.0.0.6")
+            .unwrap()
+            .with_ip_version(IpVersion::V4);
+
+        let dns = saved.be_overwrite_by(given);
+        assert_eq!(
+            dns.resolve
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+            vec![
+                "api.example.com:80:10.0.0.5",
+                "api.example.com:443:10.0.0.6"
+            ]
+        );
+        assert_eq!(dns.ip_version, Some(IpVersion::V4));
+    }
+}
@@ -0,0 +1,42 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// HTTP version forced on a request. Without one, HTTP/2 is used when the server
+/// accepts it during the TLS handshake, and HTTP/1.1 otherwise
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum HttpVersion {
+    #[serde(rename = "http1.1")]
+    Http1_1,
+    /// HTTP/2 negotiated by TLS, failing with servers which do not support it
+    #[serde(rename = "http2")]
+    Http2,
+    /// HTTP/2 from the start, without negotiation, as plaintext servers (h2c) need
+    #[serde(rename = "http2-prior-knowledge")]
+    Http2PriorKnowledge,
+}
+
+impl HttpVersion {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HttpVersion::Http1_1 => "http1.1",
+            HttpVersion::Http2 => "http2",
+            HttpVersion::Http2PriorKnowledge => "http2-prior-knowledge",
+        }
+    }
+}
+
+impl FromStr for HttpVersion {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "http1.1" => Ok(HttpVersion::Http1_1),
+            "http2" => Ok(HttpVersion::Http2),
+            "http2-prior-knowledge" => Ok(HttpVersion::Http2PriorKnowledge),
+            _ => Err(anyhow::Error::msg(format!(
+                "Invalid HTTP version '{s}', it must be 'http1.1', 'http2' or 'http2-prior-knowledge'"
+            ))),
+        }
+    }
+}
@@ -1,8 +1,15 @@
+use std::fmt::Display;
 use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Default, Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+/// Methods with its own subcommand in CLI
+pub const STANDARD_METHODS: [&str; 9] = [
+    "GET", "POST", "PUT", "DELETE", "HEAD", "PATCH", "OPTIONS", "TRACE", "CONNECT",
+];
+
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
 pub enum METHODS {
     #[default]
     GET,
@@ -11,6 +18,11 @@ pub enum METHODS {
     DELETE,
     HEAD,
     PATCH,
+    OPTIONS,
+    TRACE,
+    CONNECT,
+    /// Any other verb, like PURGE or PROPFIND. It's sent exactly as written
+    Custom(String),
 }
 
 impl FromStr for METHODS {
@@ -24,18 +36,36 @@ impl FromStr for METHODS {
             "DELETE" => METHODS::DELETE,
             "HEAD" => METHODS::HEAD,
             "PATCH" => METHODS::PATCH,
-            _ => return Err(anyhow::Error::msg("No valid METHOD")),
+            "OPTIONS" => METHODS::OPTIONS,
+            "TRACE" => METHODS::TRACE,
+            "CONNECT" => METHODS::CONNECT,
+            _ if is_token(s) => METHODS::Custom(s.to_string()),
+            _ => return Err(anyhow::Error::msg(format!("No valid METHOD: '{s}'"))),
         })
     }
 }
 
-impl ToString for METHODS {
-    fn to_string(&self) -> String {
-        self.as_str().into()
+impl TryFrom<String> for METHODS {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        METHODS::from_str(&value)
+    }
+}
+
+impl From<METHODS> for String {
+    fn from(value: METHODS) -> Self {
+        value.as_str().to_string()
+    }
+}
+
+impl Display for METHODS {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
     }
 }
 impl METHODS {
-    pub fn as_str(&self) -> &'static str {
+    pub fn as_str(&self) -> &str {
         match self {
             METHODS::GET => "GET",
             METHODS::POST => "POST",
@@ -43,6 +73,73 @@ impl METHODS {
             METHODS::DELETE => "DELETE",
             METHODS::HEAD => "HEAD",
             METHODS::PATCH => "PATCH",
+            METHODS::OPTIONS => "OPTIONS",
+            METHODS::TRACE => "TRACE",
+            METHODS::CONNECT => "CONNECT",
+            METHODS::Custom(value) => value,
         }
     }
+
+    /// Sending it many times has the same effect of sending once (RFC 9110), so it
+    /// can be safely retried
+    pub fn is_idempotent(&self) -> bool {
+        matches!(
+            self,
+            METHODS::GET
+                | METHODS::HEAD
+                | METHODS::PUT
+                | METHODS::DELETE
+                | METHODS::OPTIONS
+                | METHODS::TRACE
+        )
+    }
+}
+
+// A method must be a 'token' as defined at RFC 9110
+fn is_token(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_standard_methods_ignoring_case() {
+        assert_eq!(METHODS::OPTIONS, METHODS::from_str("options").unwrap());
+        assert_eq!(METHODS::GET, METHODS::from_str("Get").unwrap());
+    }
+
+    #[test]
+    fn test_parse_custom_methods() {
+        assert_eq!(
+            METHODS::Custom("PURGE".into()),
+            METHODS::from_str("PURGE").unwrap()
+        );
+        assert!(METHODS::from_str("NOT VALID").is_err());
+        assert!(METHODS::from_str("").is_err());
+    }
+
+    #[test]
+    fn test_serialize_as_plain_string() {
+        assert_eq!(
+            r#""PATCH""#,
+            serde_json::to_string(&METHODS::PATCH).unwrap()
+        );
+        assert_eq!(
+            r#""PURGE""#,
+            serde_json::to_string(&METHODS::Custom("PURGE".into())).unwrap()
+        );
+        assert_eq!(
+            METHODS::Custom("PURGE".into()),
+            serde_json::from_str::<METHODS>(r#""PURGE""#).unwrap()
+        );
+        assert_eq!(
+            METHODS::DELETE,
+            serde_json::from_str::<METHODS>(r#""DELETE""#).unwrap()
+        );
+    }
 }
@@ -1,4 +1,17 @@
+pub mod auth;
+pub mod body_files;
+pub mod compression;
+pub mod cookies;
+pub mod dns;
+pub mod http_version;
 pub mod methods;
+pub mod multipart;
 pub mod partial_entities;
+pub mod proxies;
+pub mod redirects;
 pub mod requests;
+pub mod retries;
+pub mod sessions;
+pub mod timeouts;
+pub mod tls;
 pub mod url;
@@ -0,0 +1,55 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+/// A part of a 'multipart/form-data' body. Files are referenced by path, their
+/// content is only read when submitting
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MultipartField {
+    Text {
+        name: String,
+        value: String,
+    },
+    File {
+        name: String,
+        path: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        content_type: Option<String>,
+    },
+}
+
+impl MultipartField {
+    pub fn name(&self) -> &str {
+        match self {
+            MultipartField::Text { name, .. } => name,
+            MultipartField::File { name, .. } => name,
+        }
+    }
+}
+
+impl Display for MultipartField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MultipartField::Text { name, value } => write!(f, "{name}={value}"),
+            MultipartField::File {
+                name,
+                path,
+                content_type: Some(content_type),
+            } => write!(f, "{name}@{path};type={content_type}"),
+            MultipartField::File { name, path, .. } => write!(f, "{name}@{path}"),
+        }
+    }
+}
+
+/// Replaces fields with the same name, keeping its position, and appends the new ones
+pub fn merge_multipart_fields(fields: &mut Vec<MultipartField>, other_fields: Vec<MultipartField>) {
+    for other_field in other_fields {
+        match fields
+            .iter_mut()
+            .find(|field| field.name() == other_field.name())
+        {
+            Some(field) => *field = other_field,
+            None => fields.push(other_field),
+        }
+    }
+}
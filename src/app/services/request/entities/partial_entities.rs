@@ -3,8 +3,18 @@ use std::str::FromStr;
 
 use serde::Serialize;
 
+use super::auth::Auth;
+use super::body_files::{resolve_body_files, BodyFileReference};
+use super::compression::BodyCompression;
+use super::dns::DnsSettings;
+use super::http_version::HttpVersion;
 use super::methods::METHODS;
+use super::proxies::ProxySettings;
+use super::redirects::RedirectPolicy;
 use super::requests::{BodyPayload, RequestData};
+use super::retries::RetryPolicy;
+use super::timeouts::Timeouts;
+use super::tls::TlsSettings;
 use super::url::{Url, UrlInfo};
 
 #[derive(Default, Clone, Debug, PartialEq, Eq, Serialize)]
@@ -13,6 +23,32 @@ pub struct PartialRequestData {
     pub method: Option<METHODS>,
     pub headers: Option<HashMap<String, String>>,
     pub body: Option<BodyPayload>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeouts: Option<Timeouts>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub redirects: Option<RedirectPolicy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retries: Option<RetryPolicy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls: Option<TlsSettings>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<ProxySettings>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth: Option<Auth>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub http_version: Option<HttpVersion>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dns: Option<DnsSettings>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unix_socket: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compress: Option<BodyCompression>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skip_decompression: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body_files: Option<Vec<BodyFileReference>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub skip_default_headers: Option<bool>,
 }
 
 impl PartialRequestData {
@@ -40,6 +76,79 @@ impl PartialRequestData {
         self.headers = Some(values.into());
         self
     }
+
+    pub fn with_timeouts(mut self, value: Timeouts) -> Self {
+        self.timeouts = Some(value);
+        self
+    }
+
+    pub fn with_redirects(mut self, value: RedirectPolicy) -> Self {
+        self.redirects = Some(value);
+        self
+    }
+
+    pub fn with_retries(mut self, value: RetryPolicy) -> Self {
+        self.retries = Some(value);
+        self
+    }
+
+    pub fn with_tls(mut self, value: TlsSettings) -> Self {
+        self.tls = Some(value);
+        self
+    }
+
+    pub fn with_proxy(mut self, value: ProxySettings) -> Self {
+        self.proxy = Some(value);
+        self
+    }
+
+    pub fn with_auth(mut self, value: Auth) -> Self {
+        self.auth = Some(value);
+        self
+    }
+
+    pub fn with_http_version(mut self, value: HttpVersion) -> Self {
+        self.http_version = Some(value);
+        self
+    }
+
+    pub fn with_dns(mut self, value: DnsSettings) -> Self {
+        self.dns = Some(value);
+        self
+    }
+
+    pub fn with_unix_socket(mut self, value: impl Into<String>) -> Self {
+        self.unix_socket = Some(value.into());
+        self
+    }
+
+    pub fn with_compress(mut self, value: BodyCompression) -> Self {
+        self.compress = Some(value);
+        self
+    }
+
+    pub fn with_skip_decompression(mut self, value: bool) -> Self {
+        self.skip_decompression = Some(value);
+        self
+    }
+
+    pub fn with_body_files(mut self, value: Vec<BodyFileReference>) -> Self {
+        self.body_files = Some(value);
+        self
+    }
+
+    /// Reads every file referenced by body, inlining its content
+    pub fn with_resolved_body_files(mut self) -> anyhow::Result<Self> {
+        if let Some(body_files) = self.body_files.take() {
+            self.body = resolve_body_files(self.body, &body_files)?;
+        }
+        Ok(self)
+    }
+
+    pub fn with_skip_default_headers(mut self, value: bool) -> Self {
+        self.skip_default_headers = Some(value);
+        self
+    }
 }
 
 impl From<RequestData> for PartialRequestData {
@@ -48,14 +157,27 @@ impl From<RequestData> for PartialRequestData {
             url: Some(value.url),
             method: Some(value.method),
             headers: Some(value.headers),
-            body: Some(value.body),
+            body: value.body,
+            timeouts: Some(value.timeouts),
+            redirects: Some(value.redirects),
+            retries: Some(value.retries),
+            tls: Some(value.tls),
+            proxy: Some(value.proxy),
+            auth: value.auth,
+            http_version: value.http_version,
+            dns: Some(value.dns),
+            unix_socket: value.unix_socket,
+            compress: value.compress,
+            skip_decompression: Some(value.skip_decompression),
+            body_files: Some(value.body_files),
+            skip_default_headers: Some(value.skip_default_headers),
         }
     }
 }
 
 impl PartialRequestData {
     pub fn to_request_data(self) -> RequestData {
-        RequestData::default()
+        let request = RequestData::default()
             .with_url(
                 self.url
                     .expect("Url is required to define a Request Data")
@@ -66,6 +188,23 @@ impl PartialRequestData {
                     .expect("METHOD is required to define a Request Data"),
             )
             .with_headers(self.headers.unwrap_or_default())
-            .with_body_payload(self.body.unwrap_or_default())
+            .with_timeouts(self.timeouts.unwrap_or_default())
+            .with_redirects(self.redirects.unwrap_or_default())
+            .with_retries(self.retries.unwrap_or_default())
+            .with_tls(self.tls.unwrap_or_default())
+            .with_proxy(self.proxy.unwrap_or_default())
+            .with_dns(self.dns.unwrap_or_default())
+            .with_body_files(self.body_files.unwrap_or_default())
+            .with_skip_decompression(self.skip_decompression.unwrap_or_default())
+            .with_skip_default_headers(self.skip_default_headers.unwrap_or_default());
+
+        RequestData {
+            body: self.body,
+            auth: self.auth,
+            http_version: self.http_version,
+            unix_socket: self.unix_socket,
+            compress: self.compress,
+            ..request
+        }
     }
 }
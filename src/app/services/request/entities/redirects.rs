@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_MAX_REDIRECTS: usize = 30;
+
+/// How redirect responses (3xx with a `Location` header) are handled.
+/// Without an explicit `follow`, redirects are not followed and the redirect
+/// response itself is returned.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RedirectPolicy {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub follow: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_redirects: Option<usize>,
+}
+
+impl RedirectPolicy {
+    pub fn with_follow(mut self, value: bool) -> Self {
+        self.follow = Some(value);
+        self
+    }
+
+    pub fn with_max_redirects(mut self, value: usize) -> Self {
+        self.max_redirects = Some(value);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == RedirectPolicy::default()
+    }
+
+    pub fn be_overwrite_by(self, other: RedirectPolicy) -> RedirectPolicy {
+        RedirectPolicy {
+            follow: other.follow.or(self.follow),
+            max_redirects: other.max_redirects.or(self.max_redirects),
+        }
+    }
+
+    pub fn should_follow(&self) -> bool {
+        self.follow.unwrap_or(false)
+    }
+
+    pub fn max(&self) -> usize {
+        self.max_redirects.unwrap_or(DEFAULT_MAX_REDIRECTS)
+    }
+}
@@ -1,20 +1,84 @@
 use std::collections::HashMap;
+use std::fmt::Display;
 use std::str::FromStr;
 use std::sync::Arc;
 
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{Map, Value};
 
+use super::auth::Auth;
+use super::body_files::{
+    merge_body_file_references, resolve_body_files, resolve_body_files_async, BodyFileReference,
+};
+use super::compression::BodyCompression;
+use super::cookies::CookieJar;
+use super::dns::DnsSettings;
+use super::http_version::HttpVersion;
+use super::multipart::{merge_multipart_fields, MultipartField};
 use super::partial_entities::PartialRequestData;
+use super::proxies::ProxySettings;
+use super::redirects::RedirectPolicy;
+use super::retries::RetryPolicy;
+use super::timeouts::Timeouts;
+use super::tls::TlsSettings;
 use super::url::{Url, UrlInfo};
 use crate::app::services::request::entities::methods::METHODS;
 
+/// Headers sent with JSON bodies, when not set by the user
+pub const JSON_DEFAULT_HEADERS: [(&str, &str); 2] = [
+    ("Content-Type", "application/json"),
+    ("Accept", "application/json, */*;q=0.5"),
+];
+
+/// Headers sent with form bodies, when not set by the user
+pub const FORM_DEFAULT_HEADERS: [(&str, &str); 1] = [(
+    "Content-Type",
+    "application/x-www-form-urlencoded; charset=utf-8",
+)];
+
 #[derive(Default, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct RequestData {
     pub url: Url,
     pub method: METHODS,
     pub headers: HashMap<String, String>,
-    pub body: BodyPayload,
+    /// None when no body was given, to not send any
+    #[serde(default)]
+    pub body: Option<BodyPayload>,
+    #[serde(default, skip_serializing_if = "Timeouts::is_empty")]
+    pub timeouts: Timeouts,
+    #[serde(default, skip_serializing_if = "RedirectPolicy::is_empty")]
+    pub redirects: RedirectPolicy,
+    #[serde(default, skip_serializing_if = "RetryPolicy::is_empty")]
+    pub retries: RetryPolicy,
+    #[serde(default, skip_serializing_if = "TlsSettings::is_empty")]
+    pub tls: TlsSettings,
+    #[serde(default, skip_serializing_if = "ProxySettings::is_empty")]
+    pub proxy: ProxySettings,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<Auth>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http_version: Option<HttpVersion>,
+    #[serde(default, skip_serializing_if = "DnsSettings::is_empty")]
+    pub dns: DnsSettings,
+    /// Path of a Unix domain socket to send the request by, instead of TCP
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unix_socket: Option<String>,
+    /// Compression of the body sent, multipart bodies are always sent as they are
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compress: Option<BodyCompression>,
+    /// Keep the body of response as received, without decoding its 'Content-Encoding'
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub skip_decompression: bool,
+    /// Body values to read from files on submit
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub body_files: Vec<BodyFileReference>,
+    /// Do not add any header by default, based on the body
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub skip_default_headers: bool,
+    /// Cookies of the session used by the request, never saved with it.
+    /// None to not send nor keep any cookie
+    #[serde(skip)]
+    pub cookies: Option<CookieJar>,
 }
 
 impl RequestData {
@@ -27,11 +91,15 @@ impl RequestData {
         self
     }
     pub fn with_body(mut self, value: impl Into<BodyPayload>) -> Self {
-        self.body = value.into();
+        self.body = Some(value.into());
         self
     }
     pub fn with_body_payload(mut self, value: BodyPayload) -> Self {
-        self.body = value;
+        self.body = Some(value);
+        self
+    }
+    pub fn without_body(mut self) -> Self {
+        self.body = None;
         self
     }
     pub fn with_method(mut self, value: METHODS) -> Self {
@@ -42,6 +110,133 @@ impl RequestData {
         self.headers = values.into();
         self
     }
+    pub fn with_timeouts(mut self, value: Timeouts) -> Self {
+        self.timeouts = value;
+        self
+    }
+    pub fn with_redirects(mut self, value: RedirectPolicy) -> Self {
+        self.redirects = value;
+        self
+    }
+    pub fn with_retries(mut self, value: RetryPolicy) -> Self {
+        self.retries = value;
+        self
+    }
+    pub fn with_tls(mut self, value: TlsSettings) -> Self {
+        self.tls = value;
+        self
+    }
+    pub fn with_proxy(mut self, value: ProxySettings) -> Self {
+        self.proxy = value;
+        self
+    }
+    pub fn with_http_version(mut self, value: HttpVersion) -> Self {
+        self.http_version = Some(value);
+        self
+    }
+    pub fn with_dns(mut self, value: DnsSettings) -> Self {
+        self.dns = value;
+        self
+    }
+    pub fn with_unix_socket(mut self, value: impl Into<String>) -> Self {
+        self.unix_socket = Some(value.into());
+        self
+    }
+    pub fn with_auth(mut self, value: Auth) -> Self {
+        self.auth = Some(value);
+        self
+    }
+    pub fn with_compress(mut self, value: BodyCompression) -> Self {
+        self.compress = Some(value);
+        self
+    }
+    pub fn with_skip_decompression(mut self, value: bool) -> Self {
+        self.skip_decompression = value;
+        self
+    }
+    pub fn with_body_files(mut self, value: Vec<BodyFileReference>) -> Self {
+        self.body_files = value;
+        self
+    }
+    pub fn with_cookies(mut self, value: CookieJar) -> Self {
+        self.cookies = Some(value);
+        self
+    }
+
+    /// Reads every file referenced by body, inlining its content
+    pub fn with_resolved_body_files(mut self) -> anyhow::Result<Self> {
+        self.body = resolve_body_files(self.body, &self.body_files)?;
+        self.body_files.clear();
+        Ok(self)
+    }
+
+    /// Same as `with_resolved_body_files`, without blocking the async runtime while reading
+    pub async fn with_resolved_body_files_async(mut self) -> anyhow::Result<Self> {
+        self.body = resolve_body_files_async(self.body, &self.body_files).await?;
+        self.body_files.clear();
+        Ok(self)
+    }
+
+    pub fn with_skip_default_headers(mut self, value: bool) -> Self {
+        self.skip_default_headers = value;
+        self
+    }
+
+    /// Headers that will be added on submit, those already set by the user are never replaced
+    pub fn default_headers(&self) -> Vec<(String, String)> {
+        if self.skip_default_headers {
+            return Vec::new();
+        }
+
+        let defaults: &[(&str, &str)] = match self.body {
+            Some(BodyPayload::Json(_)) => &JSON_DEFAULT_HEADERS,
+            Some(BodyPayload::Form(ref fields)) if !fields.is_empty() => &FORM_DEFAULT_HEADERS,
+            _ => &[],
+        };
+
+        defaults
+            .iter()
+            .filter(|(key, _)| {
+                !self
+                    .headers
+                    .keys()
+                    .any(|user_key| user_key.eq_ignore_ascii_case(key))
+            })
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    pub fn headers_with_defaults(&self) -> HashMap<String, String> {
+        let mut headers = self.headers.clone();
+        headers.extend(self.default_headers());
+
+        let auth_header = self.auth.as_ref().and_then(Auth::header_value);
+        let has_auth_header = headers
+            .keys()
+            .any(|key| key.eq_ignore_ascii_case("authorization"));
+        if let Some(value) = auth_header.filter(|_| !has_auth_header) {
+            headers.insert("Authorization".to_string(), value);
+        }
+
+        if let Some(compression) = self.body_compression() {
+            headers.retain(|key, _| !key.eq_ignore_ascii_case("content-encoding"));
+            headers.insert(
+                "Content-Encoding".to_string(),
+                compression.as_str().to_string(),
+            );
+        }
+
+        headers
+    }
+
+    /// Compression to apply on the body sent. Empty and multipart bodies are not compressed
+    pub fn body_compression(&self) -> Option<BodyCompression> {
+        match &self.body {
+            Some(BodyPayload::Multipart(_)) | None => None,
+            Some(body) if body.is_empty() => None,
+            Some(_) => self.compress,
+        }
+    }
 
     pub fn merge(mut self, other: PartialRequestData) -> Self {
         // Method
@@ -63,30 +258,180 @@ impl RequestData {
         // Headers
         self.headers.extend(other.headers.unwrap_or_default());
 
+        // Body files
+        if let Some(other_body_files) = other.body_files {
+            if other_body_files
+                .iter()
+                .any(BodyFileReference::is_whole_body)
+            {
+                self.body = None;
+            }
+            merge_body_file_references(&mut self.body_files, other_body_files);
+        }
+
         // Body
         if let Some(other_body) = other.body {
+            // New values replace the ones read from files
+            match &other_body {
+                BodyPayload::Json(Value::Object(other_map_json)) => {
+                    self.body_files.retain(|reference| {
+                        reference
+                            .keys
+                            .first()
+                            .is_none_or(|key| !other_map_json.contains_key(key))
+                    })
+                }
+                // An empty form only sets the mode of the body read from file
+                BodyPayload::Form(other_fields) if other_fields.is_empty() => {}
+                _ => self.body_files.clear(),
+            }
+
             match (self.body, other_body) {
                 (
-                    BodyPayload::Json(Value::Object(mut current_map_json)),
+                    Some(BodyPayload::Json(Value::Object(mut current_map_json))),
                     BodyPayload::Json(Value::Object(other_map_json)),
                 ) => {
                     current_map_json.extend(other_map_json);
-                    self.body = BodyPayload::Json(serde_json::Value::Object(current_map_json));
+                    self.body = Some(BodyPayload::Json(serde_json::Value::Object(
+                        current_map_json,
+                    )));
+                }
+                (Some(BodyPayload::Form(mut current_fields)), BodyPayload::Form(other_fields)) => {
+                    merge_form_fields(&mut current_fields, other_fields);
+                    self.body = Some(BodyPayload::Form(current_fields));
+                }
+                // Body items given to a saved form keep being form fields
+                (
+                    Some(BodyPayload::Form(mut current_fields)),
+                    BodyPayload::Json(Value::Object(other_map_json)),
+                ) => {
+                    merge_form_fields(
+                        &mut current_fields,
+                        json_object_to_text_fields(other_map_json),
+                    );
+                    self.body = Some(BodyPayload::Form(current_fields));
+                }
+                (
+                    Some(BodyPayload::Multipart(mut current_fields)),
+                    BodyPayload::Multipart(other_fields),
+                ) => {
+                    merge_multipart_fields(&mut current_fields, other_fields);
+                    self.body = Some(BodyPayload::Multipart(current_fields));
+                }
+                (
+                    Some(BodyPayload::Multipart(mut current_fields)),
+                    BodyPayload::Json(Value::Object(other_map_json)),
+                ) => {
+                    merge_multipart_fields(
+                        &mut current_fields,
+                        multipart_text_fields(other_map_json),
+                    );
+                    self.body = Some(BodyPayload::Multipart(current_fields));
+                }
+                // Files added to a JSON body turn it into a multipart one
+                (
+                    Some(BodyPayload::Json(Value::Object(current_map_json))),
+                    BodyPayload::Multipart(other_fields),
+                ) => {
+                    let mut current_fields = multipart_text_fields(current_map_json);
+                    merge_multipart_fields(&mut current_fields, other_fields);
+                    self.body = Some(BodyPayload::Multipart(current_fields));
                 }
                 (_, other_body) => {
-                    self.body = other_body;
+                    self.body = Some(other_body);
                 }
             };
         }
 
+        // Timeouts
+        if let Some(other_timeouts) = other.timeouts {
+            self.timeouts = self.timeouts.be_overwrite_by(other_timeouts);
+        }
+
+        // Redirects
+        if let Some(other_redirects) = other.redirects {
+            self.redirects = self.redirects.be_overwrite_by(other_redirects);
+        }
+
+        // Retries
+        if let Some(other_retries) = other.retries {
+            self.retries = self.retries.be_overwrite_by(other_retries);
+        }
+
+        // TLS
+        if let Some(other_tls) = other.tls {
+            self.tls = self.tls.be_overwrite_by(other_tls);
+        }
+
+        // Auth
+        self.auth = other.auth.or(self.auth);
+
+        // HTTP version
+        self.http_version = other.http_version.or(self.http_version);
+
+        // DNS
+        if let Some(other_dns) = other.dns {
+            self.dns = self.dns.be_overwrite_by(other_dns);
+        }
+
+        // Unix socket
+        self.unix_socket = other.unix_socket.or(self.unix_socket);
+
+        // Compression
+        self.compress = other.compress.or(self.compress);
+        self.skip_decompression = other.skip_decompression.unwrap_or(self.skip_decompression);
+
+        // Proxy
+        if let Some(other_proxy) = other.proxy {
+            self.proxy = self.proxy.be_overwrite_by(other_proxy);
+        }
+
+        // Default headers
+        self.skip_default_headers = other
+            .skip_default_headers
+            .unwrap_or(self.skip_default_headers);
+
         self
     }
 }
 
+/// Flattens a JSON object to text fields, with its non string values serialized
+pub fn json_object_to_text_fields(map: Map<String, Value>) -> Vec<(String, String)> {
+    map.into_iter()
+        .map(|(key, value)| match value {
+            Value::String(value) => (key, value),
+            value => (key, value.to_string()),
+        })
+        .collect()
+}
+
+pub fn multipart_text_fields(map: Map<String, Value>) -> Vec<MultipartField> {
+    json_object_to_text_fields(map)
+        .into_iter()
+        .map(|(name, value)| MultipartField::Text { name, value })
+        .collect()
+}
+
+pub fn merge_form_fields(fields: &mut Vec<(String, String)>, other_fields: Vec<(String, String)>) {
+    for (key, value) in other_fields {
+        match fields
+            .iter_mut()
+            .find(|(current_key, _)| *current_key == key)
+        {
+            Some(field) => field.1 = value,
+            None => fields.push((key, value)),
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum BodyPayload {
     Raw(String),
     Json(serde_json::Value),
+    /// Fields sent as 'application/x-www-form-urlencoded', in order
+    Form(Vec<(String, String)>),
+    /// Fields sent as 'multipart/form-data', in order
+    Multipart(Vec<MultipartField>),
 }
 
 impl Default for BodyPayload {
@@ -96,6 +441,20 @@ impl Default for BodyPayload {
 }
 
 impl BodyPayload {
+    pub fn is_empty(&self) -> bool {
+        match self {
+            BodyPayload::Raw(value) => value.is_empty(),
+            BodyPayload::Json(_) => false,
+            BodyPayload::Form(fields) => fields.is_empty(),
+            BodyPayload::Multipart(fields) => fields.is_empty(),
+        }
+    }
+
+    pub fn form_from_str(value: &str) -> anyhow::Result<Self> {
+        Ok(BodyPayload::Form(serde_urlencoded::from_str(value)?))
+    }
+
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(value: &str) -> Self {
         match serde_json::from_str::<Value>(value) {
             Ok(value) => BodyPayload::Json(value),
@@ -104,11 +463,19 @@ impl BodyPayload {
     }
 }
 
-impl ToString for BodyPayload {
-    fn to_string(&self) -> String {
+impl Display for BodyPayload {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            BodyPayload::Raw(value) => value.to_string(),
-            BodyPayload::Json(value) => value.to_string(),
+            BodyPayload::Raw(value) => write!(f, "{value}"),
+            BodyPayload::Json(value) => write!(f, "{value}"),
+            BodyPayload::Form(fields) => {
+                let encoded = serde_urlencoded::to_string(fields).map_err(|_| std::fmt::Error)?;
+                write!(f, "{encoded}")
+            }
+            BodyPayload::Multipart(fields) => {
+                let fields: Vec<String> = fields.iter().map(|field| field.to_string()).collect();
+                write!(f, "{}", fields.join(" "))
+            }
         }
     }
 }
@@ -180,3 +547,195 @@ impl From<RequestData> for NodeHistoryRequest {
         }
     }
 }
+
+#[cfg(test)]
+mod tests_request_data {
+    use super::*;
+
+    #[test]
+    fn test_default_headers_only_for_json_bodies() {
+        let request = RequestData::default().with_body(r#"{ "name": "John" }"#);
+        assert_eq!(
+            request.default_headers(),
+            [
+                ("Content-Type".to_string(), "application/json".to_string()),
+                (
+                    "Accept".to_string(),
+                    "application/json, */*;q=0.5".to_string()
+                ),
+            ]
+        );
+
+        let request = RequestData::default().with_body("plain text");
+        assert!(request.default_headers().is_empty());
+
+        let request = RequestData::default();
+        assert!(request.default_headers().is_empty());
+    }
+
+    #[test]
+    fn test_default_headers_do_not_override_user_headers() {
+        let request = RequestData::default()
+            .with_body(r#"{ "name": "John" }"#)
+            .with_headers([("content-type".to_string(), "text/json".to_string())]);
+
+        let headers = request.headers_with_defaults();
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers["content-type"], "text/json");
+        assert_eq!(headers["Accept"], "application/json, */*;q=0.5");
+    }
+
+    #[test]
+    fn test_skip_default_headers() {
+        let request = RequestData::default()
+            .with_body(r#"{ "name": "John" }"#)
+            .with_skip_default_headers(true);
+
+        assert!(request.default_headers().is_empty());
+    }
+
+    #[test]
+    fn test_content_encoding_of_compressed_body() {
+        let request = RequestData::default()
+            .with_body(r#"{ "name": "John" }"#)
+            .with_compress(BodyCompression::Gzip)
+            .with_skip_default_headers(true);
+        assert_eq!(
+            Some(&"gzip".to_string()),
+            request.headers_with_defaults().get("Content-Encoding")
+        );
+
+        let request = request.with_body_payload(BodyPayload::Multipart(Vec::new()));
+        assert_eq!(None, request.body_compression());
+        assert!(!request
+            .headers_with_defaults()
+            .contains_key("Content-Encoding"));
+    }
+
+    #[test]
+    fn test_form_body_is_url_encoded() {
+        let body = BodyPayload::Form(vec![
+            ("name".into(), "John Doe".into()),
+            ("job".into(), "dev&ops".into()),
+        ]);
+        assert_eq!(body.to_string(), "name=John+Doe&job=dev%26ops");
+
+        let body = BodyPayload::form_from_str("name=John+Doe&job=dev%26ops").unwrap();
+        assert_eq!(
+            body,
+            BodyPayload::Form(vec![
+                ("name".into(), "John Doe".into()),
+                ("job".into(), "dev&ops".into()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_merge_body_items_into_saved_form() {
+        let request = RequestData::default().with_body_payload(BodyPayload::Form(vec![
+            ("name".into(), "John".into()),
+            ("job".into(), "dev".into()),
+        ]));
+
+        let merged = request
+            .merge(PartialRequestData::default().with_body(r#"{ "name": "Jane", "age": 30 }"#));
+
+        assert_eq!(
+            merged.body,
+            Some(BodyPayload::Form(vec![
+                ("name".into(), "Jane".into()),
+                ("job".into(), "dev".into()),
+                ("age".into(), "30".into()),
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_merge_files_into_saved_json_body() {
+        let request = RequestData::default().with_body(r#"{ "name": "John" }"#);
+
+        let merged = request.merge(PartialRequestData::default().with_body(
+            BodyPayload::Multipart(vec![MultipartField::File {
+                name: "avatar".into(),
+                path: "/tmp/me.png".into(),
+                content_type: None,
+            }]),
+        ));
+
+        assert_eq!(
+            merged.body,
+            Some(BodyPayload::Multipart(vec![
+                MultipartField::Text {
+                    name: "name".into(),
+                    value: "John".into(),
+                },
+                MultipartField::File {
+                    name: "avatar".into(),
+                    path: "/tmp/me.png".into(),
+                    content_type: None,
+                },
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_merge_body_values_over_file_references() {
+        let request = RequestData::default().with_body_files(vec![
+            BodyFileReference {
+                keys: vec!["name".into()],
+                path: "/tmp/name.txt".into(),
+                as_json: false,
+            },
+            BodyFileReference {
+                keys: vec!["config".into()],
+                path: "/tmp/config.json".into(),
+                as_json: true,
+            },
+        ]);
+
+        let merged =
+            request.merge(PartialRequestData::default().with_body(r#"{ "name": "Jane" }"#));
+
+        assert_eq!(
+            merged.body_files,
+            vec![BodyFileReference {
+                keys: vec!["config".into()],
+                path: "/tmp/config.json".into(),
+                as_json: true,
+            }]
+        );
+
+        let merged =
+            merged.merge(
+                PartialRequestData::default().with_body_files(vec![BodyFileReference {
+                    keys: vec![],
+                    path: "/tmp/body.json".into(),
+                    as_json: false,
+                }]),
+            );
+
+        assert_eq!(merged.body, None);
+        assert_eq!(merged.body_files.len(), 1);
+    }
+
+    #[test]
+    fn test_merge_tls_settings_of_saved_request() {
+        let request = RequestData::default().with_tls(
+            TlsSettings::default()
+                .with_ca_bundle("/certs/ca.pem")
+                .with_cert("/certs/client.pem"),
+        );
+
+        let merged = request.merge(
+            PartialRequestData::default().with_tls(TlsSettings::default().with_verify(false)),
+        );
+
+        assert_eq!(
+            merged.tls,
+            TlsSettings::default()
+                .with_verify(false)
+                .with_ca_bundle("/certs/ca.pem")
+                .with_cert("/certs/client.pem")
+        );
+    }
+}
@@ -0,0 +1,164 @@
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_RETRY_BACKOFF_MS: u64 = 500;
+pub const DEFAULT_RETRY_MAX_BACKOFF_MS: u64 = 30_000;
+pub const DEFAULT_RETRY_ON_STATUSES: [u16; 4] = [429, 502, 503, 504];
+
+/// How a request failed by a transient error, as a connection reset or a 503 response,
+/// is sent again. Without `retries`, it's never retried. The wait between attempts
+/// starts at `backoff_ms` and doubles on each retry, unless the server tells how long
+/// to wait by 'Retry-After'.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backoff_ms: Option<u64>,
+    /// Longest wait between attempts, for 'Retry-After' too
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_backoff_ms: Option<u64>,
+    /// Statuses of response to retry on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_on: Option<Vec<u16>>,
+    /// Retry methods which are not idempotent, like POST and PATCH
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub all_methods: Option<bool>,
+}
+
+impl RetryPolicy {
+    pub fn with_retries(mut self, value: u32) -> Self {
+        self.retries = Some(value);
+        self
+    }
+
+    pub fn with_backoff_ms(mut self, value: u64) -> Self {
+        self.backoff_ms = Some(value);
+        self
+    }
+
+    pub fn with_max_backoff_ms(mut self, value: u64) -> Self {
+        self.max_backoff_ms = Some(value);
+        self
+    }
+
+    pub fn with_retry_on(mut self, value: Vec<u16>) -> Self {
+        self.retry_on = Some(value);
+        self
+    }
+
+    pub fn with_all_methods(mut self, value: bool) -> Self {
+        self.all_methods = Some(value);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == RetryPolicy::default()
+    }
+
+    pub fn be_overwrite_by(self, other: RetryPolicy) -> RetryPolicy {
+        RetryPolicy {
+            retries: other.retries.or(self.retries),
+            backoff_ms: other.backoff_ms.or(self.backoff_ms),
+            max_backoff_ms: other.max_backoff_ms.or(self.max_backoff_ms),
+            retry_on: other.retry_on.or(self.retry_on),
+            all_methods: other.all_methods.or(self.all_methods),
+        }
+    }
+
+    pub fn max_retries(&self) -> u32 {
+        self.retries.unwrap_or(0)
+    }
+
+    pub fn should_retry_status(&self, status: u16) -> bool {
+        match &self.retry_on {
+            Some(statuses) => statuses.contains(&status),
+            None => DEFAULT_RETRY_ON_STATUSES.contains(&status),
+        }
+    }
+
+    pub fn should_retry_method(&self, is_idempotent: bool) -> bool {
+        is_idempotent || self.all_methods.unwrap_or(false)
+    }
+
+    /// Wait before the given retry, counted from 1. 'Retry-After' of the failed
+    /// response, in seconds or as an HTTP date, is preferred over the backoff
+    pub fn wait_before(&self, retry: u32, retry_after: Option<&str>) -> Duration {
+        let max =
+            Duration::from_millis(self.max_backoff_ms.unwrap_or(DEFAULT_RETRY_MAX_BACKOFF_MS));
+
+        let wait = retry_after.and_then(parse_retry_after).unwrap_or_else(|| {
+            let backoff = self.backoff_ms.unwrap_or(DEFAULT_RETRY_BACKOFF_MS);
+            Duration::from_millis(
+                backoff.saturating_mul(2u64.saturating_pow(retry.saturating_sub(1))),
+            )
+        });
+
+        wait.min(max)
+    }
+}
+
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    match value.parse::<u64>() {
+        Ok(seconds) => Some(Duration::from_secs(seconds)),
+        Err(_) => {
+            let date = httpdate::parse_http_date(value).ok()?;
+            Some(
+                date.duration_since(SystemTime::now())
+                    .unwrap_or(Duration::ZERO),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exponential_backoff_until_max() {
+        let policy = RetryPolicy::default()
+            .with_backoff_ms(100)
+            .with_max_backoff_ms(1_000);
+
+        assert_eq!(Duration::from_millis(100), policy.wait_before(1, None));
+        assert_eq!(Duration::from_millis(200), policy.wait_before(2, None));
+        assert_eq!(Duration::from_millis(800), policy.wait_before(4, None));
+        assert_eq!(Duration::from_millis(1_000), policy.wait_before(5, None));
+        assert_eq!(Duration::from_millis(1_000), policy.wait_before(40, None));
+    }
+
+    #[test]
+    fn test_wait_given_by_retry_after() {
+        let policy = RetryPolicy::default().with_backoff_ms(100);
+
+        assert_eq!(Duration::from_secs(3), policy.wait_before(1, Some("3")));
+        assert_eq!(
+            Duration::ZERO,
+            policy.wait_before(1, Some("Thu, 01 Jan 1970 00:00:00 GMT"))
+        );
+        assert_eq!(
+            Duration::from_millis(DEFAULT_RETRY_MAX_BACKOFF_MS),
+            policy.wait_before(1, Some("3600"))
+        );
+        assert_eq!(
+            Duration::from_millis(100),
+            policy.wait_before(1, Some("soon"))
+        );
+    }
+
+    #[test]
+    fn test_statuses_and_methods_to_retry() {
+        let policy = RetryPolicy::default();
+        assert!(policy.should_retry_status(503));
+        assert!(!policy.should_retry_status(500));
+        assert!(!policy.should_retry_method(false));
+
+        let policy = policy.with_retry_on(vec![500]).with_all_methods(true);
+        assert!(policy.should_retry_status(500));
+        assert!(!policy.should_retry_status(503));
+        assert!(policy.should_retry_method(false));
+    }
+}
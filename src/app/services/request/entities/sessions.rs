@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::cookies::CookieJar;
+use super::requests::RequestData;
+use super::url::Url;
+
+/// Headers which only make sense to the request they were given
+const NON_STICKY_HEADER_PREFIXES: [&str; 4] = ["content-", "if-", "cookie", "range"];
+
+/// Named session kept between runs, with the cookies received and the headers given
+/// to its requests, like 'Authorization'
+#[derive(Default, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Session {
+    /// Headers by the host they were sent to, like 'localhost:8080'. They are only sent
+    /// again to that host, so credentials never leak to other ones
+    #[serde(default)]
+    pub headers: HashMap<String, HashMap<String, String>>,
+    #[serde(default)]
+    pub cookies: CookieJar,
+}
+
+impl Session {
+    /// Adds the headers of the request host and the cookies of session to the request.
+    /// Headers given to the request are kept over the ones of session
+    pub fn apply_to(&self, mut request: RequestData) -> RequestData {
+        let session_headers = host_of(&request).and_then(|host| self.headers.get(&host));
+
+        for (key, value) in session_headers.into_iter().flatten() {
+            let is_given = request
+                .headers
+                .keys()
+                .any(|given_key| given_key.eq_ignore_ascii_case(key));
+            if !is_given {
+                request.headers.insert(key.clone(), value.clone());
+            }
+        }
+
+        request.with_cookies(self.cookies.clone())
+    }
+
+    /// Keeps the headers sent by the request, for its host, and the cookies after its response
+    pub fn update(&mut self, request: &RequestData, cookies: Option<CookieJar>) {
+        if let Some(host) = host_of(request) {
+            let sticky_headers = request.headers.iter().filter(|(key, _)| {
+                let key = key.to_lowercase();
+                !NON_STICKY_HEADER_PREFIXES
+                    .iter()
+                    .any(|prefix| key.starts_with(prefix))
+            });
+
+            let host_headers = self.headers.entry(host).or_default();
+            for (key, value) in sticky_headers {
+                host_headers.retain(|current_key, _| !current_key.eq_ignore_ascii_case(key));
+                host_headers.insert(key.clone(), value.clone());
+            }
+        }
+
+        if let Some(cookies) = cookies {
+            self.cookies = cookies;
+        }
+    }
+}
+
+/// Host of the request URL with its port, when given, like 'localhost:8080'
+fn host_of(request: &RequestData) -> Option<String> {
+    let Url::ValidatedUrl(url) = &request.url else {
+        return None;
+    };
+    let host = url.host.as_ref()?.to_lowercase();
+    Some(match url.port {
+        Some(port) => format!("{host}:{port}"),
+        None => host,
+    })
+}
+
+/// Sessions are files of the data directory, so their names can not have paths
+pub fn validate_session_name(name: &str) -> anyhow::Result<()> {
+    let is_valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.'));
+
+    if !is_valid {
+        return Err(anyhow::Error::msg(format!(
+            "Invalid session name '{name}', it must only have letters, numbers, '-', '_' and '.', and not start with '.'"
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keep_sticky_headers_of_requests() {
+        let mut session = Session::default();
+        let request = RequestData::default()
+            .with_url("https://api.example.com/login")
+            .with_headers([
+                ("Authorization".to_string(), "Bearer abc".to_string()),
+                ("Content-Type".to_string(), "text/plain".to_string()),
+                ("If-None-Match".to_string(), "\"v1\"".to_string()),
+            ]);
+        session.update(&request, None);
+
+        assert_eq!(
+            HashMap::from([(
+                "api.example.com".to_string(),
+                HashMap::from([("Authorization".to_string(), "Bearer abc".to_string())])
+            )]),
+            session.headers
+        );
+
+        let request = RequestData::default()
+            .with_url("https://api.example.com/me")
+            .with_headers([("authorization".to_string(), "Bearer def".to_string())]);
+        let request = session.apply_to(request);
+        assert_eq!(
+            HashMap::from([("authorization".to_string(), "Bearer def".to_string())]),
+            request.headers
+        );
+        assert_eq!(Some(CookieJar::default()), request.cookies);
+    }
+
+    #[test]
+    fn test_send_headers_only_to_their_host() {
+        let mut session = Session::default();
+        let request = RequestData::default()
+            .with_url("http://api.internal:8080/login")
+            .with_headers([("Authorization".to_string(), "Bearer abc".to_string())]);
+        session.update(&request, None);
+
+        for url in [
+            "https://other.com/",
+            "http://api.internal/",
+            "http://api.internal:9090/",
+        ] {
+            let request = session.apply_to(RequestData::default().with_url(url));
+            assert!(request.headers.is_empty(), "Headers sent to {url}");
+        }
+
+        let request =
+            session.apply_to(RequestData::default().with_url("http://API.internal:8080/me"));
+        assert_eq!(
+            HashMap::from([("Authorization".to_string(), "Bearer abc".to_string())]),
+            request.headers
+        );
+    }
+
+    #[test]
+    fn test_validate_session_name() {
+        assert!(validate_session_name("app").is_ok());
+        assert!(validate_session_name("my-app_v2.prod").is_ok());
+
+        assert!(validate_session_name("").is_err());
+        assert!(validate_session_name("../config.json").is_err());
+        assert!(validate_session_name("sessions/app").is_err());
+        assert!(validate_session_name(".hidden").is_err());
+    }
+}
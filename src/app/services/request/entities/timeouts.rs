@@ -0,0 +1,62 @@
+use std::time::Duration;
+
+use anyhow::Error;
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Timeouts {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub connect_ms: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_ms: Option<u64>,
+}
+
+impl Timeouts {
+    pub fn with_total_ms(mut self, value: u64) -> Self {
+        self.total_ms = Some(value);
+        self
+    }
+
+    pub fn with_connect_ms(mut self, value: u64) -> Self {
+        self.connect_ms = Some(value);
+        self
+    }
+
+    pub fn with_read_ms(mut self, value: u64) -> Self {
+        self.read_ms = Some(value);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Timeouts::default()
+    }
+
+    pub fn be_overwrite_by(self, other: Timeouts) -> Timeouts {
+        Timeouts {
+            total_ms: other.total_ms.or(self.total_ms),
+            connect_ms: other.connect_ms.or(self.connect_ms),
+            read_ms: other.read_ms.or(self.read_ms),
+        }
+    }
+
+    pub fn total(&self) -> Option<Duration> {
+        self.total_ms.map(Duration::from_millis)
+    }
+
+    pub fn connect(&self) -> Option<Duration> {
+        self.connect_ms.map(Duration::from_millis)
+    }
+
+    pub fn read(&self) -> Option<Duration> {
+        self.read_ms.map(Duration::from_millis)
+    }
+}
+
+/// Converts a number of seconds given by the user, like `2.5`, to milliseconds
+pub fn millis_from_secs(seconds: f64) -> anyhow::Result<u64> {
+    Duration::try_from_secs_f64(seconds)
+        .map(|duration| duration.as_millis() as u64)
+        .map_err(|_| Error::msg(format!("Invalid number of seconds: {seconds}")))
+}
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+/// TLS settings of a request, all files are in PEM format.
+/// Without any of them the built-in root certificates are used to validate servers.
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TlsSettings {
+    /// Validate the certificate of server (default: true)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verify: Option<bool>,
+    /// CA certificates to trust, besides the built-in ones
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_bundle: Option<String>,
+    /// Client certificate for mutual TLS. It may also have the private key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cert: Option<String>,
+    /// Private key of client certificate, when not in the same file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cert_key: Option<String>,
+}
+
+impl TlsSettings {
+    pub fn with_verify(mut self, value: bool) -> Self {
+        self.verify = Some(value);
+        self
+    }
+
+    pub fn with_ca_bundle(mut self, value: impl Into<String>) -> Self {
+        self.ca_bundle = Some(value.into());
+        self
+    }
+
+    pub fn with_cert(mut self, value: impl Into<String>) -> Self {
+        self.cert = Some(value.into());
+        self
+    }
+
+    pub fn with_cert_key(mut self, value: impl Into<String>) -> Self {
+        self.cert_key = Some(value.into());
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == TlsSettings::default()
+    }
+
+    pub fn be_overwrite_by(self, other: TlsSettings) -> TlsSettings {
+        TlsSettings {
+            verify: other.verify.or(self.verify),
+            ca_bundle: other.ca_bundle.or(self.ca_bundle),
+            cert: other.cert.or(self.cert),
+            cert_key: other.cert_key.or(self.cert_key),
+        }
+    }
+
+    pub fn should_verify(&self) -> bool {
+        self.verify.unwrap_or(true)
+    }
+}
@@ -1,6 +1,9 @@
+use tokio::sync::mpsc::UnboundedSender;
 use tokio::sync::oneshot;
 
-use super::entities::Response;
+use super::downloads::DownloadTarget;
+use super::entities::{FailedAttempt, Response};
+use super::repository_client::TaskRunningRequest;
 use super::service::WebClientInstance;
 use crate::app::service_commands::Command;
 use crate::app::services::request::entities::requests::RequestData;
@@ -10,16 +13,46 @@ pub type CommandWebClient<Resp> = Command<WebClientInstance, Resp>;
 pub struct CommandsFactory;
 
 impl CommandsFactory {
-    pub fn submit(request: RequestData) -> CommandWebClient<anyhow::Result<Response>> {
+    pub fn submit(
+        request: RequestData,
+        attempts: Option<UnboundedSender<FailedAttempt>>,
+    ) -> CommandWebClient<anyhow::Result<Response>> {
         let (tx, rx) = oneshot::channel();
         Command::from(move |mut service: WebClientInstance| {
-            let task = service.submit_async(request);
-            tokio::task::spawn(async move {
-                let response = task.await.map_err(|e| e.into()).and_then(|resp| resp);
-                tx.send(response).ok();
-            });
+            let task = service.submit_async(request, attempts);
+            tokio::task::spawn(send_response_of_task(task, tx));
             service
         })
         .with_response(rx)
     }
+
+    pub fn download(
+        request: RequestData,
+        target: DownloadTarget,
+        attempts: Option<UnboundedSender<FailedAttempt>>,
+    ) -> CommandWebClient<anyhow::Result<Response>> {
+        let (tx, rx) = oneshot::channel();
+        Command::from(move |mut service: WebClientInstance| {
+            let task = service.download_async(request, target, attempts);
+            tokio::task::spawn(send_response_of_task(task, tx));
+            service
+        })
+        .with_response(rx)
+    }
+}
+
+/// Sends the response of the task when it ends. If the receiver is dropped before, as when
+/// the user cancels the request, the task is aborted
+async fn send_response_of_task(
+    mut task: TaskRunningRequest,
+    mut tx: oneshot::Sender<anyhow::Result<Response>>,
+) {
+    let response = tokio::select! {
+        response = &mut task => response.map_err(|e| e.into()).and_then(|resp| resp),
+        _ = tx.closed() => {
+            task.abort();
+            return;
+        }
+    };
+    tx.send(response).ok();
 }
@@ -0,0 +1,160 @@
+use std::io::Write;
+
+use flate2::write::{GzDecoder, ZlibDecoder};
+
+/// Value of 'Accept-Encoding' sent when the response should be decoded
+pub const ACCEPT_ENCODING: &str = "gzip, deflate, br";
+
+/// Decoder of a response body compressed by server, fed chunk by chunk as it arrives
+pub enum ContentDecoder {
+    Gzip(GzDecoder<Vec<u8>>),
+    Deflate(ZlibDecoder<Vec<u8>>),
+    Brotli(Box<brotli::DecompressorWriter<Vec<u8>>>),
+}
+
+impl ContentDecoder {
+    /// Decoder of the 'Content-Encoding' of response. None for 'identity', for unknown
+    /// encodings and for more than one encoding applied, whose bodies are kept as received
+    pub fn for_content_encoding(content_encoding: &str) -> Option<Self> {
+        match content_encoding.trim().to_lowercase().as_str() {
+            "gzip" | "x-gzip" => Some(ContentDecoder::Gzip(GzDecoder::new(Vec::new()))),
+            "deflate" => Some(ContentDecoder::Deflate(ZlibDecoder::new(Vec::new()))),
+            "br" => Some(ContentDecoder::Brotli(Box::new(
+                brotli::DecompressorWriter::new(Vec::new(), 4096),
+            ))),
+            _ => None,
+        }
+    }
+
+    /// Decodes the chunk, returning the content decoded so far
+    pub fn decode(&mut self, chunk: &[u8]) -> anyhow::Result<Vec<u8>> {
+        let decoded = match self {
+            ContentDecoder::Gzip(decoder) => {
+                decoder.write_all(chunk)?;
+                decoder.get_mut()
+            }
+            ContentDecoder::Deflate(decoder) => {
+                decoder.write_all(chunk)?;
+                decoder.get_mut()
+            }
+            ContentDecoder::Brotli(decoder) => {
+                decoder.write_all(chunk)?;
+                decoder.get_mut()
+            }
+        };
+        Ok(std::mem::take(decoded))
+    }
+
+    /// Content left to decode, failing when the body was truncated
+    pub fn finish(self) -> anyhow::Result<Vec<u8>> {
+        let decoded = match self {
+            ContentDecoder::Gzip(decoder) => decoder.finish()?,
+            ContentDecoder::Deflate(decoder) => decoder.finish()?,
+            ContentDecoder::Brotli(mut decoder) => {
+                decoder.close()?;
+                std::mem::take(decoder.get_mut())
+            }
+        };
+        Ok(decoded)
+    }
+}
+
+/// Body of a response read chunk by chunk, decoded when there is a decoder for it
+pub struct BodyReader {
+    decoder: Option<ContentDecoder>,
+    body: Vec<u8>,
+    wire_size: u64,
+}
+
+impl BodyReader {
+    pub fn new(decoder: Option<ContentDecoder>) -> Self {
+        Self {
+            decoder,
+            body: Vec::new(),
+            wire_size: 0,
+        }
+    }
+
+    pub fn read(&mut self, chunk: &[u8]) -> anyhow::Result<()> {
+        self.wire_size += chunk.len() as u64;
+        match self.decoder.as_mut() {
+            Some(decoder) => self.body.extend(decoder.decode(chunk)?),
+            None => self.body.extend_from_slice(chunk),
+        }
+        Ok(())
+    }
+
+    /// Body read, with its size on wire when it was decoded
+    pub fn finish(mut self) -> anyhow::Result<(Vec<u8>, Option<u64>)> {
+        // Responses without body, like the ones of HEAD, may still inform the encoding
+        let is_decoded = self.decoder.is_some() && self.wire_size > 0;
+        if let Some(decoder) = self.decoder.filter(|_| is_decoded) {
+            self.body.extend(decoder.finish()?);
+        }
+        Ok((self.body, is_decoded.then_some(self.wire_size)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use flate2::write::{GzEncoder, ZlibEncoder};
+
+    use super::*;
+
+    const CONTENT: &str =
+        r#"{ "name": "treq", "description": "A cli client to make HTTP requests" }"#;
+
+    fn decode_in_chunks(mut decoder: ContentDecoder, encoded: &[u8]) -> Vec<u8> {
+        let mut decoded = Vec::new();
+        for chunk in encoded.chunks(7) {
+            decoded.extend(decoder.decode(chunk).unwrap());
+        }
+        decoded.extend(decoder.finish().unwrap());
+        decoded
+    }
+
+    #[test]
+    fn test_decode_gzip_deflate_and_brotli() {
+        let mut gzip = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(CONTENT.as_bytes()).unwrap();
+        let decoder = ContentDecoder::for_content_encoding("gzip").unwrap();
+        assert_eq!(
+            CONTENT.as_bytes(),
+            decode_in_chunks(decoder, &gzip.finish().unwrap())
+        );
+
+        let mut deflate = ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        deflate.write_all(CONTENT.as_bytes()).unwrap();
+        let decoder = ContentDecoder::for_content_encoding("Deflate").unwrap();
+        assert_eq!(
+            CONTENT.as_bytes(),
+            decode_in_chunks(decoder, &deflate.finish().unwrap())
+        );
+
+        let mut brotli = brotli::CompressorWriter::new(Vec::new(), 4096, 5, 22);
+        brotli.write_all(CONTENT.as_bytes()).unwrap();
+        let decoder = ContentDecoder::for_content_encoding("br").unwrap();
+        assert_eq!(
+            CONTENT.as_bytes(),
+            decode_in_chunks(decoder, &brotli.into_inner())
+        );
+    }
+
+    #[test]
+    fn test_keep_unknown_encodings() {
+        assert!(ContentDecoder::for_content_encoding("identity").is_none());
+        assert!(ContentDecoder::for_content_encoding("gzip, br").is_none());
+        assert!(ContentDecoder::for_content_encoding("zstd").is_none());
+    }
+
+    #[test]
+    fn test_fail_on_truncated_content() {
+        let mut gzip = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(CONTENT.as_bytes()).unwrap();
+        let encoded = gzip.finish().unwrap();
+
+        let mut decoder = ContentDecoder::for_content_encoding("gzip").unwrap();
+        decoder.decode(&encoded[..encoded.len() / 2]).unwrap();
+        assert!(decoder.finish().is_err());
+    }
+}
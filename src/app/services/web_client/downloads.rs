@@ -0,0 +1,196 @@
+use std::path::{Path, PathBuf};
+
+use percent_encoding::percent_decode_str;
+use tokio::sync::mpsc::UnboundedSender;
+
+/// File where the body of a successful response is streamed to, instead of keeping
+/// it in memory
+#[derive(Clone, Debug, Default)]
+pub struct DownloadTarget {
+    /// When not given, the file name is taken from the response
+    pub path: Option<PathBuf>,
+    /// Continue a previous download of `path`, asking the server only for the missing bytes
+    pub resume: bool,
+    pub progress: Option<UnboundedSender<DownloadProgress>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DownloadProgress {
+    Started {
+        /// Size of the complete file, if informed by server
+        total_bytes: Option<u64>,
+        resumed_from: u64,
+    },
+    Received(u64),
+}
+
+/// File written by a download
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DownloadedFile {
+    pub path: PathBuf,
+    /// Bytes written by this download, without the ones already in file when resuming
+    pub bytes_written: u64,
+    pub resumed_from: u64,
+}
+
+impl DownloadTarget {
+    pub fn with_path(mut self, value: impl Into<PathBuf>) -> Self {
+        self.path = Some(value.into());
+        self
+    }
+
+    pub fn with_resume(mut self, value: bool) -> Self {
+        self.resume = value;
+        self
+    }
+
+    pub fn with_progress(mut self, value: UnboundedSender<DownloadProgress>) -> Self {
+        self.progress = Some(value);
+        self
+    }
+
+    /// Bytes already downloaded, where the download should continue from
+    pub fn resume_offset(&self) -> u64 {
+        match (&self.path, self.resume) {
+            (Some(path), true) => std::fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+            _ => 0,
+        }
+    }
+
+    pub fn report(&self, progress: DownloadProgress) {
+        if let Some(sender) = &self.progress {
+            sender.send(progress).ok();
+        }
+    }
+}
+
+/// Name of file to save a response, from its 'Content-Disposition' header or,
+/// if it has none, from the last segment of URL path
+pub fn file_name_for_response(
+    content_disposition: Option<&str>,
+    url_path: &str,
+    content_type: Option<&str>,
+) -> String {
+    content_disposition
+        .and_then(file_name_from_content_disposition)
+        .or_else(|| url_path.rsplit('/').next().and_then(sanitize_file_name))
+        .unwrap_or_else(|| {
+            let mime = content_type
+                .and_then(|content_type| content_type.split(';').next())
+                .map(str::trim)
+                .unwrap_or_default();
+            let subtype = mime.rsplit('/').next().unwrap_or_default();
+
+            // Prefers the extension equal to the subtype, like "html" over "htm"
+            let extension = mime_guess::get_mime_extensions_str(mime).and_then(|extensions| {
+                extensions
+                    .iter()
+                    .find(|extension| **extension == subtype)
+                    .or(extensions.first())
+            });
+
+            match extension {
+                Some(extension) => format!("index.{extension}"),
+                None => "index".to_string(),
+            }
+        })
+}
+
+/// Gets the file name of header, preferring the encoded 'filename*' over 'filename'
+pub fn file_name_from_content_disposition(header: &str) -> Option<String> {
+    let params: Vec<(&str, &str)> = header
+        .split(';')
+        .skip(1)
+        .filter_map(|param| param.split_once('='))
+        .map(|(key, value)| (key.trim(), value.trim()))
+        .collect();
+
+    let encoded_name = params
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("filename*"))
+        .and_then(|(_, value)| value.split_once("''"))
+        .map(|(_, name)| percent_decode_str(name).decode_utf8_lossy().to_string());
+
+    let name = params
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("filename"))
+        .map(|(_, value)| value.trim_matches('"').to_string());
+
+    encoded_name
+        .or(name)
+        .as_deref()
+        .and_then(sanitize_file_name)
+}
+
+/// Adds a numeric suffix ('-1', '-2', ...) to the path while there is a file with its name
+pub fn unique_path(path: PathBuf) -> PathBuf {
+    if !path.exists() {
+        return path;
+    }
+
+    (1..)
+        .map(|suffix| PathBuf::from(format!("{}-{suffix}", path.display())))
+        .find(|path| !path.exists())
+        .unwrap()
+}
+
+// Names given by server can not point to other directories
+fn sanitize_file_name(name: &str) -> Option<String> {
+    let name = Path::new(name.trim())
+        .file_name()?
+        .to_string_lossy()
+        .to_string();
+    (!name.is_empty() && name != "..").then_some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_name_from_content_disposition() {
+        assert_eq!(
+            Some("report.pdf".to_string()),
+            file_name_from_content_disposition(r#"attachment; filename="report.pdf""#)
+        );
+        assert_eq!(
+            Some("relatório 1.pdf".to_string()),
+            file_name_from_content_disposition(
+                r#"attachment; filename="fallback.pdf"; filename*=UTF-8''relat%C3%B3rio%201.pdf"#
+            )
+        );
+        assert_eq!(
+            Some("passwd".to_string()),
+            file_name_from_content_disposition(r#"attachment; filename="../../etc/passwd""#)
+        );
+        assert_eq!(None, file_name_from_content_disposition("inline"));
+    }
+
+    #[test]
+    fn test_file_name_for_response() {
+        assert_eq!(
+            "data.json",
+            file_name_for_response(Some(r#"attachment; filename=data.json"#), "/file", None)
+        );
+        assert_eq!(
+            "archive.tar.gz",
+            file_name_for_response(None, "/releases/archive.tar.gz", None)
+        );
+        assert_eq!(
+            "index.html",
+            file_name_for_response(None, "/", Some("text/html; charset=utf-8"))
+        );
+        assert_eq!("index", file_name_for_response(None, "/", None));
+    }
+
+    #[test]
+    fn test_unique_path() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.txt");
+        assert_eq!(path, unique_path(path.clone()));
+
+        std::fs::write(&path, "").unwrap();
+        std::fs::write(dir.path().join("file.txt-1"), "").unwrap();
+        assert_eq!(dir.path().join("file.txt-2"), unique_path(path));
+    }
+}
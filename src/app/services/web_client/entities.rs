@@ -1,3 +1,15 @@
+use std::time::Duration;
+
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+use serde::Serialize;
+
+use super::downloads::DownloadedFile;
+use crate::app::services::request::entities::cookies::CookieJar;
+use crate::app::services::request::entities::proxies::ProxySettings;
+use crate::app::services::request::entities::redirects::RedirectPolicy;
+use crate::app::services::request::entities::timeouts::Timeouts;
+use crate::app::services::request::entities::tls::TlsSettings;
+
 #[derive(Default, Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub enum ResponseStage {
     #[default]
@@ -7,16 +19,182 @@ pub enum ResponseStage {
     Finished,
 
     Cancelled,
+    TimedOut,
     InternalError,
 }
 
 #[derive(Default, Clone, Debug, PartialEq, Eq)]
 pub struct Response {
     pub status: i32,
+    /// Version of HTTP used by the connection, like 'HTTP/2.0'
+    pub version: Option<String>,
     pub response_time_ms: u64,
+    /// Whether the request was sent over a connection opened before, when it is known
+    pub reused_connection: Option<bool>,
+    /// Phases of the last request sent, the one which got this response
+    pub timings: Timings,
     pub headers: Vec<(String, String)>,
+    /// Body decoded as text, empty when the content is binary
     pub body: String,
+    /// Body as received, or decoded from its 'Content-Encoding' when it had one
+    pub body_bytes: Vec<u8>,
+    /// Size of body as sent by server, only when it was decoded
+    pub wire_size: Option<u64>,
     pub stage: ResponseStage,
+    /// Redirect responses followed until reaching this one, in order
+    pub redirect_history: Vec<Response>,
+    /// Times the request was sent, retries included
+    pub attempts: u32,
+    /// Attempts retried before this response, in order
+    pub failed_attempts: Vec<FailedAttempt>,
+    /// File where the body was saved, instead of being kept in `body`
+    pub download: Option<DownloadedFile>,
+    /// Proxy the request was sent through, with its password hidden
+    pub proxy: Option<String>,
+    /// Address of the peer connected to, the Unix socket path on those
+    pub remote_addr: Option<String>,
+    /// Cookies of the request after storing the ones received, when it had any jar
+    pub cookies: Option<CookieJar>,
+}
+
+/// Attempt of a request that failed by a transient error, and so was sent again
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+pub struct FailedAttempt {
+    /// Status of the response, None when the request got no response
+    pub status: Option<i32>,
+    /// Error of the request, when it got no response
+    pub error: Option<String>,
+    /// Wait before sending it again
+    pub wait_ms: u64,
+}
+
+/// Time spent on each phase of a request, in milliseconds
+#[derive(Default, Copy, Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Timings {
+    /// Lookup of host. None when there was none, as for reused connections and IP hosts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dns_ms: Option<u64>,
+    /// TCP connect, with the CONNECT tunnel of proxy. None on reused connections
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub connect_ms: Option<u64>,
+    /// TLS handshake. None on reused and plaintext connections
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tls_ms: Option<u64>,
+    /// Time to first byte, since the request started to be sent until its headers arrived
+    pub ttfb_ms: u64,
+    /// Transfer of the body
+    pub download_ms: u64,
+}
+
+/// Settings of the long-lived HTTP client shared by every submitted request.
+/// `None` values keep the defaults of the underlying client.
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+pub struct HttpClientConfig {
+    pub pool_idle_timeout: Option<Duration>,
+    pub pool_max_idle_per_host: Option<usize>,
+    pub tcp_keepalive: Option<Duration>,
+    /// Timeouts used by requests which do not define their own
+    pub timeouts: Timeouts,
+    /// Redirect policy used by requests which do not define their own
+    pub redirects: RedirectPolicy,
+    /// TLS settings used by requests which do not define their own
+    pub tls: TlsSettings,
+    /// Proxies used by requests which do not define their own
+    pub proxy: ProxySettings,
+}
+
+impl HttpClientConfig {
+    pub fn with_pool_idle_timeout(mut self, value: Duration) -> Self {
+        self.pool_idle_timeout = Some(value);
+        self
+    }
+
+    pub fn with_pool_max_idle_per_host(mut self, value: usize) -> Self {
+        self.pool_max_idle_per_host = Some(value);
+        self
+    }
+
+    pub fn with_tcp_keepalive(mut self, value: Duration) -> Self {
+        self.tcp_keepalive = Some(value);
+        self
+    }
+
+    pub fn with_timeouts(mut self, value: Timeouts) -> Self {
+        self.timeouts = value;
+        self
+    }
+
+    pub fn with_redirects(mut self, value: RedirectPolicy) -> Self {
+        self.redirects = value;
+        self
+    }
+
+    pub fn with_tls(mut self, value: TlsSettings) -> Self {
+        self.tls = value;
+        self
+    }
+
+    pub fn with_proxy(mut self, value: ProxySettings) -> Self {
+        self.proxy = value;
+        self
+    }
+}
+
+impl Response {
+    pub fn header(&self, key: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn content_type(&self) -> Option<&str> {
+        self.header("content-type")
+    }
+
+    /// Size of the body kept, or saved to file when downloaded
+    pub fn body_size(&self) -> u64 {
+        match &self.download {
+            Some(download) => download.bytes_written,
+            None => self.body_bytes.len() as u64,
+        }
+    }
+
+    /// Body which can not be shown as text, like images or archives
+    pub fn is_binary(&self) -> bool {
+        decode_text(&self.body_bytes, self.content_type()).is_none()
+    }
+}
+
+/// Content decoded as text with the charset of its 'Content-Type', or of its BOM, and
+/// as UTF-8 when it has none. None when the content is binary: it has NUL bytes, which
+/// text formats never have out of UTF-16, or it is not valid in its charset
+pub fn decode_text(bytes: &[u8], content_type: Option<&str>) -> Option<String> {
+    let declared_encoding = content_type
+        .and_then(charset_of_content_type)
+        .and_then(|charset| Encoding::for_label(charset.as_bytes()))
+        .unwrap_or(UTF_8);
+    let (encoding, content) = match Encoding::for_bom(bytes) {
+        Some((encoding, bom_length)) => (encoding, &bytes[bom_length..]),
+        None => (declared_encoding, bytes),
+    };
+
+    if encoding != UTF_16LE && encoding != UTF_16BE && content.contains(&0) {
+        return None;
+    }
+    encoding
+        .decode_without_bom_handling_and_without_replacement(content)
+        .map(|text| text.into_owned())
+}
+
+/// Value of the 'charset' parameter, like 'iso-8859-1' of 'text/html; charset=iso-8859-1'
+fn charset_of_content_type(content_type: &str) -> Option<&str> {
+    content_type.split(';').skip(1).find_map(|param| {
+        let (key, value) = param.split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case("charset")
+            .then(|| value.trim().trim_matches('"'))
+    })
 }
 
 pub fn get_status_code_message(status_code: i32) -> &'static str {
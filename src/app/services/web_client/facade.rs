@@ -1,8 +1,22 @@
+use tokio::sync::mpsc::UnboundedSender;
 use tokio::task::JoinHandle;
 
-use super::entities::Response;
+use super::downloads::DownloadTarget;
+use super::entities::{FailedAttempt, Response};
 use crate::app::services::request::entities::requests::RequestData;
 
 pub trait WebClientFacade: Send {
-    fn submit_async(&mut self, request: RequestData) -> JoinHandle<anyhow::Result<Response>>;
+    /// Submits the request, telling each of its failed attempts to `attempts`, if given,
+    /// as soon as it fails
+    fn submit_async(
+        &mut self,
+        request: RequestData,
+        attempts: Option<UnboundedSender<FailedAttempt>>,
+    ) -> JoinHandle<anyhow::Result<Response>>;
+    fn download_async(
+        &mut self,
+        request: RequestData,
+        target: DownloadTarget,
+        attempts: Option<UnboundedSender<FailedAttempt>>,
+    ) -> JoinHandle<anyhow::Result<Response>>;
 }
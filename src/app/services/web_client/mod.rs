@@ -1,4 +1,6 @@
 pub mod commands;
+pub mod content_encoding;
+pub mod downloads;
 pub mod entities;
 pub mod facade;
 pub mod repository_client;
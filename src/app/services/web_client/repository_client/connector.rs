@@ -0,0 +1,499 @@
+use std::future::Future;
+use std::io::{self, BufReader};
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, SystemTime};
+
+use anyhow::Error;
+use hyper::client::connect::{Connected, Connection};
+use hyper::service::Service;
+use hyper::Uri;
+use rustls::client::{ServerCertVerified, ServerCertVerifier};
+use rustls::{Certificate, ClientConfig, OwnedTrustAnchor, PrivateKey, RootCertStore, ServerName};
+use socket2::{SockRef, TcpKeepalive};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadBuf};
+use tokio::net::{TcpStream, UnixStream};
+use tokio::time::Instant;
+use tokio_rustls::TlsConnector;
+
+use crate::app::services::request::entities::dns::DnsSettings;
+use crate::app::services::request::entities::http_version::HttpVersion;
+use crate::app::services::request::entities::proxies::{proxy_authorization, ProxySettings};
+use crate::app::services::request::entities::tls::TlsSettings;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+static NEXT_CONNECTION_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Opens the connections of a client, timing each phase of them: the lookup of host,
+/// the TCP connect and the TLS handshake. The lookup follows the resolve rules of the
+/// host and port connected to, and requests of proxies are tunneled by CONNECT when
+/// the target is HTTPS. With a Unix socket, every connection is opened to it instead.
+#[derive(Clone)]
+pub struct TimedConnector {
+    dns: DnsSettings,
+    proxy: ProxySettings,
+    tls: Arc<ClientConfig>,
+    connect_timeout: Option<Duration>,
+    tcp_keepalive: Option<Duration>,
+    unix_socket: Option<String>,
+    requires_http2: bool,
+}
+
+/// Connection a response was received on, added to the extensions of response
+#[derive(Clone, Debug)]
+pub struct ConnectionInfo {
+    /// Unique among the connections opened by the process
+    pub id: u64,
+    /// Address connected to, the one of proxy when there is one, or the Unix socket path
+    pub remote_addr: String,
+    /// Lookup of host, None when its address was already known
+    pub dns_time: Option<Duration>,
+    /// Connect, with the CONNECT tunnel of proxy when there is one
+    pub connect_time: Duration,
+    /// TLS handshake, None on plaintext connections
+    pub tls_time: Option<Duration>,
+}
+
+impl TimedConnector {
+    pub fn init(
+        dns: DnsSettings,
+        proxy: ProxySettings,
+        tls: ClientConfig,
+        connect_timeout: Option<Duration>,
+        tcp_keepalive: Option<Duration>,
+    ) -> Self {
+        Self {
+            dns,
+            proxy,
+            tls: Arc::new(tls),
+            connect_timeout,
+            tcp_keepalive,
+            unix_socket: None,
+            requires_http2: false,
+        }
+    }
+
+    /// Opens the connections to the Unix socket, whatever the host, and never through proxies
+    pub fn with_unix_socket(mut self, value: Option<String>) -> Self {
+        self.unix_socket = value;
+        self
+    }
+
+    /// Fails the connections on which the server does not agree to speak HTTP/2
+    pub fn with_requires_http2(mut self, value: bool) -> Self {
+        self.requires_http2 = value;
+        self
+    }
+
+    async fn connect(self, uri: Uri) -> Result<TimedStream, BoxError> {
+        let is_https = uri.scheme_str() == Some("https");
+        let host = uri
+            .host()
+            .ok_or("URL without host")?
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_string();
+        let port = uri.port_u16().unwrap_or(if is_https { 443 } else { 80 });
+
+        let proxy = self
+            .proxy
+            .proxy_for(uri.scheme_str().unwrap_or_default(), &host)
+            .filter(|_| self.unix_socket.is_none())
+            .map(|url| url::Url::parse(url).map(|parsed_url| (url, parsed_url)))
+            .transpose()?;
+        let (connect_host, connect_port) = match &proxy {
+            Some((_, proxy_url)) => (
+                proxy_url
+                    .host_str()
+                    .unwrap_or_default()
+                    .trim_start_matches('[')
+                    .trim_end_matches(']')
+                    .to_string(),
+                proxy_url.port_or_known_default().unwrap_or_default(),
+            ),
+            None => (host.clone(), port),
+        };
+
+        let connect_start;
+        let (mut io, remote_addr, dns_time): (Box<dyn Io>, _, _) = match &self.unix_socket {
+            Some(socket_path) => {
+                connect_start = Instant::now();
+                let stream = UnixStream::connect(socket_path).await.map_err(|err| {
+                    format!("Could not connect to Unix socket '{socket_path}': {err}")
+                })?;
+                (Box::new(stream), socket_path.clone(), None)
+            }
+            None => {
+                let (addrs, dns_time) = self.resolve(&connect_host, connect_port).await?;
+                connect_start = Instant::now();
+                let tcp = self.connect_tcp(&addrs).await?;
+                let remote_addr = tcp.peer_addr()?.to_string();
+                (Box::new(tcp), remote_addr, dns_time)
+            }
+        };
+        let mut connect_time = connect_start.elapsed();
+        let mut tls_time = None;
+
+        if let Some((proxy_url, parsed_url)) = &proxy {
+            if parsed_url.scheme() == "https" {
+                let handshake_start = Instant::now();
+                (io, _) = self.handshake(io, &connect_host, true).await?;
+                tls_time = Some(handshake_start.elapsed());
+            }
+            if is_https {
+                let tunnel_start = Instant::now();
+                tunnel(&mut io, &host, port, proxy_authorization(proxy_url)).await?;
+                connect_time += tunnel_start.elapsed();
+            }
+        }
+
+        let mut is_h2 = false;
+        if is_https {
+            let handshake_start = Instant::now();
+            (io, is_h2) = self.handshake(io, &host, false).await?;
+            tls_time = Some(tls_time.unwrap_or_default() + handshake_start.elapsed());
+        }
+        if self.requires_http2 && !is_h2 {
+            return Err(Box::new(Http2NotAgreed));
+        }
+
+        Ok(TimedStream {
+            io,
+            // Plaintext requests through proxy are sent to it with the whole URL
+            is_proxied: proxy.is_some() && !is_https,
+            is_h2,
+            info: ConnectionInfo {
+                id: NEXT_CONNECTION_ID.fetch_add(1, Ordering::SeqCst),
+                remote_addr,
+                dns_time,
+                connect_time,
+                tls_time,
+            },
+        })
+    }
+
+    /// Addresses of host to connect to, with the time taken to look them up
+    async fn resolve(
+        &self,
+        host: &str,
+        port: u16,
+    ) -> Result<(Vec<SocketAddr>, Option<Duration>), BoxError> {
+        let (addrs, dns_time) = if let Some(addrs) = self.dns.addrs_for(host, port) {
+            (addrs?, None)
+        } else if let Ok(ip) = host.parse::<IpAddr>() {
+            (vec![SocketAddr::new(ip, port)], None)
+        } else {
+            let lookup_start = Instant::now();
+            let addrs = tokio::net::lookup_host((host, port)).await?.collect();
+            (addrs, Some(lookup_start.elapsed()))
+        };
+
+        let addrs: Vec<SocketAddr> = addrs
+            .into_iter()
+            .filter(|addr| self.dns.accepts(&addr.ip()))
+            .collect();
+        if addrs.is_empty() {
+            let ip_version = self
+                .dns
+                .ip_version
+                .map(|v| v.to_string())
+                .unwrap_or_default();
+            return Err(format!("No {ip_version} address found for '{host}'").into());
+        }
+
+        Ok((addrs, dns_time))
+    }
+
+    /// Connects to the first address accepting it
+    async fn connect_tcp(&self, addrs: &[SocketAddr]) -> io::Result<TcpStream> {
+        let mut last_error = None;
+
+        for addr in addrs {
+            match TcpStream::connect(addr).await {
+                Ok(stream) => {
+                    stream.set_nodelay(true)?;
+                    if let Some(interval) = self.tcp_keepalive {
+                        SockRef::from(&stream)
+                            .set_tcp_keepalive(&TcpKeepalive::new().with_time(interval))?;
+                    }
+                    return Ok(stream);
+                }
+                Err(err) => last_error = Some(err),
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| io::Error::from(io::ErrorKind::AddrNotAvailable)))
+    }
+
+    /// TLS handshake with the host, returning whether HTTP/2 was agreed. Proxies are
+    /// only spoken to by HTTP/1.1
+    async fn handshake(
+        &self,
+        io: Box<dyn Io>,
+        host: &str,
+        is_proxy: bool,
+    ) -> Result<(Box<dyn Io>, bool), BoxError> {
+        let config = if is_proxy {
+            let mut config = (*self.tls).clone();
+            config.alpn_protocols = vec![b"http/1.1".to_vec()];
+            Arc::new(config)
+        } else {
+            self.tls.clone()
+        };
+
+        let server_name = ServerName::try_from(host)?;
+        let stream = TlsConnector::from(config).connect(server_name, io).await?;
+        let is_h2 = stream.get_ref().1.alpn_protocol() == Some(b"h2");
+
+        Ok((Box::new(stream), is_h2))
+    }
+}
+
+impl Service<Uri> for TimedConnector {
+    type Response = TimedStream;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<TimedStream, BoxError>> + Send>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        let connector = self.clone();
+
+        Box::pin(async move {
+            match connector.connect_timeout {
+                Some(timeout) => tokio::time::timeout(timeout, connector.connect(uri)).await?,
+                None => connector.connect(uri).await,
+            }
+        })
+    }
+}
+
+/// Failure of a connection which must speak HTTP/2, as the server did not agree to it
+#[derive(Debug)]
+pub struct Http2NotAgreed;
+
+impl std::fmt::Display for Http2NotAgreed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Server did not agree to use HTTP/2")
+    }
+}
+
+impl std::error::Error for Http2NotAgreed {}
+
+/// Asks the proxy for a tunnel to the host, which is then used as a connection to it
+async fn tunnel(
+    io: &mut Box<dyn Io>,
+    host: &str,
+    port: u16,
+    authorization: Option<String>,
+) -> Result<(), BoxError> {
+    let authority = match host.parse::<IpAddr>() {
+        Ok(IpAddr::V6(_)) => format!("[{host}]:{port}"),
+        _ => format!("{host}:{port}"),
+    };
+
+    let mut request = format!("CONNECT {authority} HTTP/1.1\r\nHost: {authority}\r\n");
+    if let Some(authorization) = authorization {
+        request.push_str(&format!("Proxy-Authorization: {authorization}\r\n"));
+    }
+    request.push_str("\r\n");
+    io.write_all(request.as_bytes()).await?;
+
+    // Nothing else is sent by the proxy until the tunnel is used
+    let mut response = Vec::new();
+    let mut buf = [0; 1024];
+    while !response.ends_with(b"\r\n\r\n") {
+        let read = io.read(&mut buf).await?;
+        if read == 0 {
+            return Err(format!("Proxy closed the connection asked to '{authority}'").into());
+        }
+        response.extend_from_slice(&buf[..read]);
+    }
+
+    let response = String::from_utf8_lossy(&response);
+    let status_line = response.lines().next().unwrap_or_default();
+    match status_line.split_whitespace().nth(1) {
+        Some(status) if status.starts_with('2') => Ok(()),
+        _ => Err(format!("Proxy refused the tunnel to '{authority}': {status_line}").into()),
+    }
+}
+
+/// TLS config of a client, trusting the built-in root certificates besides the ones
+/// of settings
+pub fn tls_config(
+    settings: &TlsSettings,
+    http_version: Option<HttpVersion>,
+) -> anyhow::Result<ClientConfig> {
+    let mut roots = RootCertStore::empty();
+    roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|anchor| {
+        OwnedTrustAnchor::from_subject_spki_name_constraints(
+            anchor.subject,
+            anchor.spki,
+            anchor.name_constraints,
+        )
+    }));
+    if let Some(path) = &settings.ca_bundle {
+        let invalid_bundle =
+            |err: &dyn std::fmt::Display| Error::msg(format!("Invalid CA bundle '{path}': {err}"));
+        let certificates = rustls_pemfile::certs(&mut BufReader::new(
+            read_pem_file(path, "CA bundle")?.as_slice(),
+        ))
+        .map_err(|err| invalid_bundle(&err))?;
+        if certificates.is_empty() {
+            return Err(invalid_bundle(&"no certificate found"));
+        }
+        for certificate in certificates {
+            roots
+                .add(&Certificate(certificate))
+                .map_err(|err| invalid_bundle(&err))?;
+        }
+    }
+
+    let builder = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots);
+    let mut config = match &settings.cert {
+        Some(path) => {
+            let invalid_certificate = |err: &dyn std::fmt::Display| {
+                Error::msg(format!("Invalid client certificate '{path}': {err}"))
+            };
+
+            // The key may be in the same file of certificate
+            let mut pem = read_pem_file(path, "client certificate")?;
+            if let Some(key_path) = &settings.cert_key {
+                pem.push(b'\n');
+                pem.extend(read_pem_file(key_path, "client certificate key")?);
+            }
+
+            let mut certificates = Vec::new();
+            let mut key = None;
+            let items = rustls_pemfile::read_all(&mut BufReader::new(pem.as_slice()))
+                .map_err(|err| invalid_certificate(&err))?;
+            for item in items {
+                match item {
+                    rustls_pemfile::Item::X509Certificate(certificate) => {
+                        certificates.push(Certificate(certificate))
+                    }
+                    rustls_pemfile::Item::PKCS8Key(der)
+                    | rustls_pemfile::Item::RSAKey(der)
+                    | rustls_pemfile::Item::ECKey(der) => key = key.or(Some(PrivateKey(der))),
+                    _ => {}
+                }
+            }
+            let key = key.ok_or_else(|| invalid_certificate(&"no private key found"))?;
+
+            builder
+                .with_client_auth_cert(certificates, key)
+                .map_err(|err| invalid_certificate(&err))?
+        }
+        None => builder.with_no_client_auth(),
+    };
+
+    if !settings.should_verify() {
+        config
+            .dangerous()
+            .set_certificate_verifier(Arc::new(NoCertificateVerification));
+    }
+
+    config.alpn_protocols = match http_version {
+        Some(HttpVersion::Http1_1) => vec![b"http/1.1".to_vec()],
+        Some(HttpVersion::Http2PriorKnowledge) => vec![b"h2".to_vec()],
+        Some(HttpVersion::Http2) | None => vec![b"h2".to_vec(), b"http/1.1".to_vec()],
+    };
+
+    Ok(config)
+}
+
+fn read_pem_file(path: &str, description: &str) -> anyhow::Result<Vec<u8>> {
+    std::fs::read(path)
+        .map_err(|err| Error::msg(format!("Could not read {description} '{path}': {err}")))
+}
+
+/// Accepts any certificate of server, for '--verify no'
+struct NoCertificateVerification;
+
+impl ServerCertVerifier for NoCertificateVerification {
+    fn verify_server_cert(
+        &self,
+        _: &Certificate,
+        _: &[Certificate],
+        _: &ServerName,
+        _: &mut dyn Iterator<Item = &[u8]>,
+        _: &[u8],
+        _: SystemTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+}
+
+trait Io: AsyncRead + AsyncWrite + Send + Unpin {}
+
+impl<T: AsyncRead + AsyncWrite + Send + Unpin> Io for T {}
+
+/// Connection opened by `TimedConnector`, which tells hyper how it was opened
+pub struct TimedStream {
+    io: Box<dyn Io>,
+    is_proxied: bool,
+    is_h2: bool,
+    info: ConnectionInfo,
+}
+
+impl Connection for TimedStream {
+    fn connected(&self) -> Connected {
+        let connected = Connected::new()
+            .proxy(self.is_proxied)
+            .extra(self.info.clone());
+
+        if self.is_h2 {
+            connected.negotiated_h2()
+        } else {
+            connected
+        }
+    }
+}
+
+impl AsyncRead for TimedStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.io).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for TimedStream {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.io).poll_write(cx, buf)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.io).poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.io.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.io).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.io).poll_shutdown(cx)
+    }
+}
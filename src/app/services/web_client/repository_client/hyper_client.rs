@@ -0,0 +1,1127 @@
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::task::Poll;
+use std::time::Duration;
+
+use anyhow::Error;
+use futures_util::stream::{self, BoxStream, StreamExt};
+use hyper::body::{Bytes, HttpBody};
+use hyper::header::{
+    HeaderMap, HeaderName, HeaderValue, ACCEPT, ACCEPT_ENCODING, AUTHORIZATION,
+    CONTENT_DISPOSITION, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE, COOKIE, LOCATION,
+    PROXY_AUTHORIZATION, RANGE, SET_COOKIE, TRANSFER_ENCODING, WWW_AUTHENTICATE,
+};
+use hyper::{Body, Method, StatusCode, Uri, Version};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::Notify;
+use tokio_util::io::ReaderStream;
+
+use super::super::content_encoding::{self, BodyReader, ContentDecoder};
+use super::super::downloads::{
+    file_name_for_response, unique_path, DownloadProgress, DownloadTarget, DownloadedFile,
+};
+use super::super::entities::{
+    decode_text, FailedAttempt, HttpClientConfig, Response, ResponseStage, Timings,
+};
+use super::connector::{tls_config, ConnectionInfo, Http2NotAgreed, TimedConnector};
+use super::{HttpClientRepository, TaskRunningRequest};
+use crate::app::services::auth::aws_sigv4::AwsSigner;
+use crate::app::services::request::entities::auth::{Auth, AuthType};
+use crate::app::services::request::entities::cookies::CookieJar;
+use crate::app::services::request::entities::dns::DnsSettings;
+use crate::app::services::request::entities::http_version::HttpVersion;
+use crate::app::services::request::entities::methods::METHODS;
+use crate::app::services::request::entities::multipart::MultipartField;
+use crate::app::services::request::entities::proxies::{
+    display_proxy_url, proxy_authorization, ProxySettings,
+};
+use crate::app::services::request::entities::redirects::RedirectPolicy;
+use crate::app::services::request::entities::requests::{BodyPayload, RequestData};
+use crate::app::services::request::entities::retries::RetryPolicy;
+use crate::app::services::request::entities::timeouts::Timeouts;
+use crate::app::services::request::entities::tls::TlsSettings;
+use crate::utils::time::unix_now;
+use crate::utils::uuid::UUID;
+
+type Client = hyper::Client<TimedConnector, Body>;
+
+/// Size of the chunks in which a body in memory is sent, so the end of its upload is
+/// told close to when it really happens
+const UPLOAD_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Repository backed by long-lived hyper clients. Clients (and so their pool of
+/// keep-alive connections) live as long as the repository and are shared by every
+/// submitted request asking for the same client-level settings.
+#[derive(Clone)]
+pub struct HyperClientRepository {
+    config: HttpClientConfig,
+    clients: Arc<Clients>,
+    /// Told of each failed attempt of the requests, before they are retried
+    attempts: Option<UnboundedSender<FailedAttempt>>,
+}
+
+/// Clients built so far, by the settings they were built with
+struct Clients {
+    config: HttpClientConfig,
+    built: Mutex<HashMap<ClientSettings, Client>>,
+    connections: Arc<ConnectionsTracker>,
+}
+
+/// Settings which can only be defined when building a client, not per request
+#[derive(Default, Clone, Debug, PartialEq, Eq, Hash)]
+struct ClientSettings {
+    connect_timeout: Option<Duration>,
+    tls: TlsSettings,
+    proxy: ProxySettings,
+    http_version: Option<HttpVersion>,
+    dns: DnsSettings,
+    unix_socket: Option<String>,
+}
+
+impl ClientSettings {
+    fn from_request(
+        timeouts: &Timeouts,
+        tls: &TlsSettings,
+        proxy: &ProxySettings,
+        http_version: Option<HttpVersion>,
+        dns: &DnsSettings,
+    ) -> Self {
+        Self {
+            connect_timeout: timeouts.connect(),
+            tls: tls.clone(),
+            proxy: proxy.clone(),
+            http_version,
+            dns: dns.clone(),
+            unix_socket: None,
+        }
+    }
+}
+
+impl HyperClientRepository {
+    pub fn init(config: HttpClientConfig) -> anyhow::Result<Self> {
+        let clients = Clients {
+            config: config.clone(),
+            built: Mutex::default(),
+            connections: Arc::new(ConnectionsTracker::default()),
+        };
+
+        // Build the default client right away, so an invalid config fails early
+        clients.client_for(ClientSettings::from_request(
+            &config.timeouts,
+            &config.tls,
+            &config.proxy,
+            None,
+            &DnsSettings::default(),
+        ))?;
+
+        Ok(Self {
+            config,
+            clients: Arc::new(clients),
+            attempts: None,
+        })
+    }
+}
+
+impl Clients {
+    fn client_for(&self, settings: ClientSettings) -> anyhow::Result<Client> {
+        let mut clients = self.built.lock().unwrap();
+
+        if let Some(client) = clients.get(&settings) {
+            return Ok(client.clone());
+        }
+
+        let client = self.build_client(&settings)?;
+        clients.insert(settings, client.clone());
+        Ok(client)
+    }
+
+    fn build_client(&self, settings: &ClientSettings) -> anyhow::Result<Client> {
+        // Proxies of environment are already part of settings, as the ones with lowest priority
+        let proxy = &settings.proxy;
+        for url in proxy.http.iter().chain(proxy.https.iter()) {
+            let proxy_url = url::Url::parse(url)
+                .map_err(|err| Error::msg(format!("Invalid proxy '{url}': {err}")))?;
+            if !matches!(proxy_url.scheme(), "http" | "https") {
+                return Err(Error::msg(format!(
+                    "Invalid proxy '{url}': only HTTP and HTTPS proxies are supported"
+                )));
+            }
+        }
+
+        // Redirects are followed by hand, to keep the history of them. The connector
+        // applies the resolve rules by the host and port of each connection
+        let connector = TimedConnector::init(
+            settings.dns.clone(),
+            settings.proxy.clone(),
+            tls_config(&settings.tls, settings.http_version)?,
+            settings.connect_timeout,
+            self.config.tcp_keepalive,
+        )
+        .with_unix_socket(settings.unix_socket.clone())
+        .with_requires_http2(settings.http_version == Some(HttpVersion::Http2));
+
+        let mut builder = hyper::Client::builder();
+        if let Some(timeout) = self.config.pool_idle_timeout {
+            builder.pool_idle_timeout(timeout);
+        }
+        if let Some(max) = self.config.pool_max_idle_per_host {
+            builder.pool_max_idle_per_host(max);
+        }
+
+        // Forcing HTTP/2 over TLS is done by request, as the client must still negotiate it
+        if settings.http_version == Some(HttpVersion::Http2PriorKnowledge) {
+            builder.http2_only(true);
+        }
+
+        Ok(builder.build(connector))
+    }
+}
+
+impl Default for HyperClientRepository {
+    fn default() -> Self {
+        Self::init(HttpClientConfig::default()).expect("Default HTTP client should be buildable")
+    }
+}
+
+impl HttpClientRepository for HyperClientRepository {
+    fn submit_request(&self, request: RequestData) -> TaskRunningRequest {
+        self.submit(request, None)
+    }
+
+    fn download_request(&self, request: RequestData, target: DownloadTarget) -> TaskRunningRequest {
+        self.submit(request, Some(target))
+    }
+
+    fn reporting_attempts(
+        &self,
+        attempts: UnboundedSender<FailedAttempt>,
+    ) -> Box<dyn HttpClientRepository> {
+        Box::new(Self {
+            attempts: Some(attempts),
+            ..self.clone()
+        })
+    }
+}
+
+impl HyperClientRepository {
+    fn submit(&self, request: RequestData, download: Option<DownloadTarget>) -> TaskRunningRequest {
+        let timeouts = self.config.timeouts.be_overwrite_by(request.timeouts);
+        let redirects = self.config.redirects.be_overwrite_by(request.redirects);
+        let retries = request.retries.clone();
+        let tls = self.config.tls.clone().be_overwrite_by(request.tls.clone());
+        // Requests by a Unix socket never go through proxies
+        let proxy = match request.unix_socket {
+            Some(_) => ProxySettings::default(),
+            None => self
+                .config
+                .proxy
+                .clone()
+                .be_overwrite_by(request.proxy.clone()),
+        };
+        let http_version = request.http_version;
+        let url = request.url.to_string();
+        let client = self.clients.client_for(ClientSettings {
+            unix_socket: request.unix_socket.clone(),
+            ..ClientSettings::from_request(&timeouts, &tls, &proxy, http_version, &request.dns)
+        });
+        let connections = self.clients.connections.clone();
+        let attempts = self.attempts.clone();
+
+        tokio::task::spawn(async move {
+            let submission = RequestSubmission {
+                client: client?,
+                connections,
+                read_timeout: timeouts.read(),
+                redirects,
+                retries,
+                proxy,
+                download,
+                attempts,
+            };
+
+            let now = tokio::time::Instant::now();
+
+            let response = match timeouts.total() {
+                Some(total_timeout) => {
+                    tokio::time::timeout(total_timeout, submission.run_with_retries(request))
+                        .await
+                        .map_err(anyhow::Error::from)
+                        .and_then(|response| response)
+                }
+                None => submission.run_with_retries(request).await,
+            };
+
+            match response {
+                Err(err) if is_timeout_error(&err) => Ok(Response {
+                    response_time_ms: now.elapsed().as_millis() as u64,
+                    stage: ResponseStage::TimedOut,
+                    ..Default::default()
+                }),
+                Err(err) => match certificate_error(&err) {
+                    Some(cause) => Err(Error::msg(format!(
+                        "Could not verify the TLS certificate of '{url}': {cause}. \
+                         Use '--ca-bundle' to trust its CA, or '--verify no' to skip verification"
+                    ))),
+                    None if is_unsupported_version_error(&err) => Err(Error::msg(format!(
+                        "Server of '{url}' did not agree to use HTTP/2. \
+                             Use '--http2-prior-knowledge' for plaintext servers (h2c)"
+                    ))),
+                    None => Err(err),
+                },
+                response => response,
+            }
+        })
+    }
+
+    fn create_header_map(headers: HashMap<String, String>) -> HeaderMap {
+        let mut header_map = HeaderMap::new();
+
+        for (key, value) in headers.into_iter() {
+            header_map.insert(
+                HeaderName::from_str(&key).unwrap(),
+                HeaderValue::from_str(&value).unwrap(),
+            );
+        }
+
+        header_map
+    }
+
+    fn convert_to_app_response_head(
+        response: &hyper::Response<Body>,
+        response_time_ms: u64,
+        connection: &ConnectionUse,
+    ) -> Response {
+        let status: i32 = response.status().as_u16().into();
+        let version = format!("{:?}", response.version());
+        let mut headers: Vec<(String, String)> = response
+            .headers()
+            .iter()
+            .map(|(key, value)| {
+                (
+                    key.as_str().to_string(),
+                    value.to_str().unwrap_or_default().to_string(),
+                )
+            })
+            .collect();
+
+        headers.sort_by(|(k1, _), (k2, _)| k1.cmp(k2));
+
+        Response {
+            status,
+            version: Some(version),
+            response_time_ms,
+            reused_connection: connection.reused,
+            timings: connection.timings(response_time_ms),
+            remote_addr: connection
+                .info
+                .as_ref()
+                .map(|info| info.remote_addr.clone()),
+            headers,
+            stage: ResponseStage::Finished,
+            ..Default::default()
+        }
+    }
+
+    async fn convert_to_app_response(
+        mut response: hyper::Response<Body>,
+        response_time_ms: u64,
+        connection: &ConnectionUse,
+        read_timeout: Option<Duration>,
+        decompress: bool,
+    ) -> anyhow::Result<Response> {
+        let app_response = HyperClientRepository::convert_to_app_response_head(
+            &response,
+            response_time_ms,
+            connection,
+        );
+
+        let transfer_start = tokio::time::Instant::now();
+        let mut body_reader = BodyReader::new(decoder_of_response(&response, decompress));
+        while let Some(chunk) = with_read_timeout(read_timeout, next_chunk(&mut response)).await? {
+            body_reader.read(&chunk)?;
+        }
+        let (body, wire_size) = body_reader.finish()?;
+        let text = decode_text(&body, app_response.content_type()).unwrap_or_default();
+
+        Ok(Response {
+            body: text,
+            body_bytes: body,
+            wire_size,
+            timings: Timings {
+                download_ms: transfer_start.elapsed().as_millis() as u64,
+                ..app_response.timings
+            },
+            ..app_response
+        })
+    }
+
+    /// Writes the body to the target file chunk by chunk, reporting the progress.
+    /// A resumed download is appended to the file, unless the server sends the whole
+    /// content again
+    async fn download_to_file(
+        mut response: hyper::Response<Body>,
+        url: &url::Url,
+        response_time_ms: u64,
+        connection: &ConnectionUse,
+        read_timeout: Option<Duration>,
+        decompress: bool,
+        target: DownloadTarget,
+    ) -> anyhow::Result<Response> {
+        let app_response = HyperClientRepository::convert_to_app_response_head(
+            &response,
+            response_time_ms,
+            connection,
+        );
+
+        let resumed_from = match response.status() {
+            StatusCode::PARTIAL_CONTENT => target.resume_offset(),
+            _ => 0,
+        };
+
+        let path = target.path.clone().unwrap_or_else(|| {
+            unique_path(
+                file_name_for_response(
+                    app_response.header(CONTENT_DISPOSITION.as_str()),
+                    url.path(),
+                    app_response.content_type(),
+                )
+                .into(),
+            )
+        });
+
+        let file = if resumed_from > 0 {
+            tokio::fs::OpenOptions::new().append(true).open(&path).await
+        } else {
+            tokio::fs::File::create(&path).await
+        };
+        let mut file = file.map_err(|err| {
+            Error::msg(format!("Could not write file '{}': {err}", path.display()))
+        })?;
+
+        target.report(DownloadProgress::Started {
+            total_bytes: response
+                .body()
+                .size_hint()
+                .exact()
+                .map(|length| length + resumed_from),
+            resumed_from,
+        });
+
+        // A resumed part can not be decoded apart from the beginning of content
+        let transfer_start = tokio::time::Instant::now();
+        let mut decoder = decoder_of_response(&response, decompress && resumed_from == 0);
+
+        let mut bytes_written = 0;
+        let mut wire_size = 0;
+        while let Some(chunk) = with_read_timeout(read_timeout, next_chunk(&mut response)).await? {
+            let content = match decoder.as_mut() {
+                Some(decoder) => decoder.decode(&chunk)?,
+                None => chunk.to_vec(),
+            };
+            file.write_all(&content).await?;
+            bytes_written += content.len() as u64;
+            wire_size += chunk.len() as u64;
+            target.report(DownloadProgress::Received(chunk.len() as u64));
+        }
+        let is_decoded = decoder.is_some() && wire_size > 0;
+        if let Some(decoder) = decoder.filter(|_| is_decoded) {
+            let content = decoder.finish()?;
+            file.write_all(&content).await?;
+            bytes_written += content.len() as u64;
+        }
+        file.flush().await?;
+
+        Ok(Response {
+            download: Some(DownloadedFile {
+                path,
+                bytes_written,
+                resumed_from,
+            }),
+            wire_size: is_decoded.then_some(wire_size),
+            timings: Timings {
+                download_ms: transfer_start.elapsed().as_millis() as u64,
+                ..app_response.timings
+            },
+            ..app_response
+        })
+    }
+}
+
+/// A single submit of a request, following its redirects when asked to
+struct RequestSubmission {
+    client: Client,
+    connections: Arc<ConnectionsTracker>,
+    read_timeout: Option<Duration>,
+    redirects: RedirectPolicy,
+    retries: RetryPolicy,
+    proxy: ProxySettings,
+    download: Option<DownloadTarget>,
+    attempts: Option<UnboundedSender<FailedAttempt>>,
+}
+
+impl RequestSubmission {
+    /// Sends the request again while it fails by a transient error, as long as its
+    /// retry policy allows
+    async fn run_with_retries(&self, request: RequestData) -> anyhow::Result<Response> {
+        let can_retry = self
+            .retries
+            .should_retry_method(request.method.is_idempotent());
+        let mut failed_attempts: Vec<FailedAttempt> = Vec::new();
+
+        loop {
+            let retry = failed_attempts.len() as u32 + 1;
+            let result = self.run(request.clone()).await;
+
+            let failed_attempt = match &result {
+                Ok(response) if self.retries.should_retry_status(response.status as u16) => {
+                    Some(FailedAttempt {
+                        status: Some(response.status),
+                        wait_ms: self.wait_before(retry, response.header("retry-after")),
+                        ..Default::default()
+                    })
+                }
+                Err(err) if is_transient_error(err) => Some(FailedAttempt {
+                    error: Some(err.to_string()),
+                    wait_ms: self.wait_before(retry, None),
+                    ..Default::default()
+                }),
+                _ => None,
+            }
+            .filter(|_| can_retry && retry <= self.retries.max_retries());
+
+            match failed_attempt {
+                Some(failed_attempt) => {
+                    if let Some(attempts) = &self.attempts {
+                        attempts.send(failed_attempt.clone()).ok();
+                    }
+                    tokio::time::sleep(Duration::from_millis(failed_attempt.wait_ms)).await;
+                    failed_attempts.push(failed_attempt);
+                }
+                None => {
+                    let attempts = retry;
+                    return match result {
+                        Ok(response) => Ok(Response {
+                            attempts,
+                            failed_attempts,
+                            ..response
+                        }),
+                        Err(err) if attempts > 1 => {
+                            let message = format!("{err} (after {attempts} attempts)");
+                            Err(err.context(message))
+                        }
+                        Err(err) => Err(err),
+                    };
+                }
+            }
+        }
+    }
+
+    fn wait_before(&self, retry: u32, retry_after: Option<&str>) -> u64 {
+        self.retries.wait_before(retry, retry_after).as_millis() as u64
+    }
+
+    async fn run(&self, request: RequestData) -> anyhow::Result<Response> {
+        let request = with_aws_signature(request.with_resolved_body_files_async().await?)?;
+        let mut cookies = request.cookies.clone();
+        let auth = request.auth.clone();
+        let url = url::Url::parse(&request.url.to_string())?;
+        let headers = request.headers_with_defaults();
+        let compression = request.body_compression();
+        let method = request.method;
+        let decompress = !request.skip_decompression;
+        let sets_accept_encoding = headers
+            .keys()
+            .any(|key| key.eq_ignore_ascii_case(ACCEPT_ENCODING.as_str()));
+
+        let mut request_to_send = HttpRequest {
+            method: Method::from_str(method.as_str())?,
+            url,
+            version: Version::HTTP_11,
+            headers: HyperClientRepository::create_header_map(headers),
+            body: RequestBody::Empty,
+        };
+
+        // Any content is accepted unless told otherwise
+        if !request_to_send.headers.contains_key(ACCEPT) {
+            request_to_send
+                .headers
+                .insert(ACCEPT, HeaderValue::from_static("*/*"));
+        }
+
+        // An empty body is only sent explicitly on methods that usually carry one
+        let body = request
+            .body
+            .filter(|body| !body.is_empty() || !matches!(method, METHODS::GET | METHODS::HEAD));
+        if let Some(BodyPayload::Multipart(fields)) = body {
+            let boundary = UUID::new_random().0.simple().to_string();
+            request_to_send.headers.insert(
+                CONTENT_TYPE,
+                HeaderValue::from_str(&format!("multipart/form-data; boundary={boundary}"))?,
+            );
+            request_to_send.body = RequestBody::Multipart { fields, boundary };
+        } else if let Some(body) = body {
+            request_to_send.body = match compression {
+                Some(compression) => {
+                    RequestBody::Bytes(compression.compress(body.to_string().as_bytes())?.into())
+                }
+                None => RequestBody::Bytes(body.to_string().into()),
+            };
+        }
+
+        // Downloads ask for the content as it is, keeping their progress and resume exact
+        if decompress && self.download.is_none() && !sets_accept_encoding {
+            request_to_send.headers.insert(
+                ACCEPT_ENCODING,
+                HeaderValue::from_static(content_encoding::ACCEPT_ENCODING),
+            );
+        }
+
+        let resume_offset = self
+            .download
+            .as_ref()
+            .map_or(0, DownloadTarget::resume_offset);
+        if resume_offset > 0 {
+            request_to_send.headers.insert(
+                RANGE,
+                HeaderValue::from_str(&format!("bytes={resume_offset}-"))?,
+            );
+        }
+        if request.http_version == Some(HttpVersion::Http2) {
+            request_to_send.version = Version::HTTP_2;
+        }
+
+        let origin = request_to_send.url.origin();
+        let mut request = request_to_send;
+        let mut redirect_history: Vec<Response> = Vec::new();
+
+        let now = tokio::time::Instant::now();
+
+        loop {
+            let request_to_redirect = request.clone();
+            if let Some(cookies) = &cookies {
+                add_jar_cookies(&mut request, cookies)?;
+            }
+            let proxy_url = self
+                .proxy
+                .proxy_for(
+                    request.url.scheme(),
+                    request.url.host_str().unwrap_or_default(),
+                )
+                .map(str::to_string);
+
+            // Plaintext requests are sent to the proxy itself, so they carry its credentials
+            let proxy_credentials = proxy_url
+                .as_deref()
+                .filter(|_| request.url.scheme() == "http")
+                .and_then(proxy_authorization);
+            if let Some(credentials) = proxy_credentials {
+                if !request.headers.contains_key(PROXY_AUTHORIZATION) {
+                    request
+                        .headers
+                        .insert(PROXY_AUTHORIZATION, HeaderValue::from_str(&credentials)?);
+                }
+            }
+
+            let hop_url = request.url.clone();
+            let hop_start = tokio::time::Instant::now();
+            // Challenges are only answered for the origin the credentials were given to
+            let hop_auth = auth.as_ref().filter(|_| request.url.origin() == origin);
+            let (response, connection) = self.send_with_auth(request, hop_auth).await?;
+            let hop_time_ms = hop_start.elapsed().as_millis() as u64;
+
+            // Cookies set by a redirect response are already sent to the next URL
+            if let Some(cookies) = cookies.as_mut() {
+                let set_cookie_headers = response
+                    .headers()
+                    .get_all(SET_COOKIE)
+                    .iter()
+                    .filter_map(|value| value.to_str().ok());
+                cookies.store_from_headers(&hop_url, set_cookie_headers);
+            }
+
+            let next_request = self
+                .redirects
+                .should_follow()
+                .then(|| redirect_request(request_to_redirect, &response))
+                .flatten()
+                .transpose()?;
+
+            match next_request {
+                Some(next_request) => {
+                    if redirect_history.len() >= self.redirects.max() {
+                        return Err(Error::msg(format!(
+                            "Too many redirects (max: {})",
+                            self.redirects.max()
+                        )));
+                    }
+
+                    redirect_history.push(HyperClientRepository::convert_to_app_response_head(
+                        &response,
+                        hop_time_ms,
+                        &connection,
+                    ));
+                    request = next_request;
+                }
+                None => {
+                    // Error responses are never saved to file, to be shown instead
+                    let mut response = match self.download.clone() {
+                        Some(target) if response.status().is_success() => {
+                            HyperClientRepository::download_to_file(
+                                response,
+                                &hop_url,
+                                hop_time_ms,
+                                &connection,
+                                self.read_timeout,
+                                decompress,
+                                target,
+                            )
+                            .await?
+                        }
+                        _ => {
+                            HyperClientRepository::convert_to_app_response(
+                                response,
+                                hop_time_ms,
+                                &connection,
+                                self.read_timeout,
+                                decompress,
+                            )
+                            .await?
+                        }
+                    };
+                    response.response_time_ms = now.elapsed().as_millis() as u64;
+                    response.redirect_history = redirect_history;
+                    response.proxy = proxy_url.as_deref().map(display_proxy_url);
+                    response.cookies = cookies.take();
+                    return Ok(response);
+                }
+            }
+        }
+    }
+
+    /// Sends the request, answering to the challenge of server when it asks for digest auth
+    async fn send_with_auth(
+        &self,
+        request: HttpRequest,
+        auth: Option<&Auth>,
+    ) -> anyhow::Result<(hyper::Response<Body>, ConnectionUse)> {
+        let Some(auth) = auth.filter(|auth| auth.auth_type == AuthType::Digest) else {
+            return self.send(&request).await;
+        };
+
+        let (response, connection) = self.send(&request).await?;
+
+        let challenge = response
+            .headers()
+            .get_all(WWW_AUTHENTICATE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .find(|value| value.trim_start().to_lowercase().starts_with("digest "))
+            .map(String::from);
+
+        match (response.status(), challenge) {
+            (StatusCode::UNAUTHORIZED, Some(challenge)) => {
+                let mut request = request;
+                let uri = match request.url.query() {
+                    Some(query) => format!("{}?{query}", request.url.path()),
+                    None => request.url.path().to_string(),
+                };
+                let cnonce = UUID::new_random().0.simple().to_string();
+                let answer =
+                    auth.digest_answer(&challenge, request.method.as_str(), &uri, &cnonce)?;
+
+                request
+                    .headers
+                    .insert(AUTHORIZATION, HeaderValue::from_str(&answer)?);
+                self.send(&request).await
+            }
+            _ => Ok((response, connection)),
+        }
+    }
+
+    async fn send(
+        &self,
+        request: &HttpRequest,
+    ) -> anyhow::Result<(hyper::Response<Body>, ConnectionUse)> {
+        let uploaded = Arc::new(Notify::new());
+        let request = request.to_hyper_request(uploaded.clone()).await?;
+
+        // The read timeout only starts once the body was sent, so a slow upload or
+        // connect is not taken for a server slow to answer
+        let mut response = self.client.request(request);
+        let response = tokio::select! {
+            response = &mut response => response?,
+            _ = uploaded.notified() => with_read_timeout(self.read_timeout, response).await?,
+        };
+
+        let info = response.extensions().get::<ConnectionInfo>().cloned();
+        let connection = ConnectionUse {
+            reused: info
+                .as_ref()
+                .map(|info| self.connections.mark_as_used(info.id)),
+            info,
+        };
+
+        Ok((response, connection))
+    }
+}
+
+/// Request to be sent, which is built again for each attempt, redirect and answer
+/// to an auth challenge
+#[derive(Clone, Debug)]
+struct HttpRequest {
+    method: Method,
+    url: url::Url,
+    version: Version,
+    headers: HeaderMap,
+    body: RequestBody,
+}
+
+#[derive(Clone, Debug)]
+enum RequestBody {
+    Empty,
+    Bytes(Bytes),
+    /// Files are streamed from disk while sending, they are never fully loaded in memory
+    Multipart {
+        fields: Vec<MultipartField>,
+        boundary: String,
+    },
+}
+
+impl HttpRequest {
+    /// Request for hyper, whose body notifies `uploaded` once it was all handed to the
+    /// connection
+    async fn to_hyper_request(
+        &self,
+        uploaded: Arc<Notify>,
+    ) -> anyhow::Result<hyper::Request<Body>> {
+        let mut headers = self.headers.clone();
+        let body = match &self.body {
+            RequestBody::Empty => {
+                uploaded.notify_one();
+                Body::empty()
+            }
+            RequestBody::Bytes(bytes) => {
+                // Always set, as hyper sends no framing header at all for a streamed body
+                // of unknown length
+                headers.insert(CONTENT_LENGTH, HeaderValue::from(bytes.len()));
+                let chunks = (0..bytes.len())
+                    .step_by(UPLOAD_CHUNK_SIZE)
+                    .map(|start| {
+                        Ok(bytes.slice(start..(start + UPLOAD_CHUNK_SIZE).min(bytes.len())))
+                    })
+                    .collect::<Vec<_>>();
+                body_notifying_end(stream::iter(chunks).boxed(), uploaded)
+            }
+            RequestBody::Multipart { fields, boundary } => {
+                let (parts, length) = multipart_body(fields, boundary).await?;
+                headers.insert(CONTENT_LENGTH, HeaderValue::from(length));
+                body_notifying_end(parts, uploaded)
+            }
+        };
+
+        let mut url = self.url.clone();
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(Error::msg(format!(
+                "URL scheme '{}' is not supported, only 'http' and 'https' are",
+                url.scheme()
+            )));
+        }
+        url.set_fragment(None);
+        let _ = url.set_username("");
+        let _ = url.set_password(None);
+
+        let mut request = hyper::Request::builder()
+            .method(self.method.clone())
+            .uri(Uri::from_str(url.as_str())?)
+            .version(self.version)
+            .body(body)?;
+        *request.headers_mut() = headers;
+
+        Ok(request)
+    }
+}
+
+/// How the connection which sent a request was got
+#[derive(Clone, Debug)]
+struct ConnectionUse {
+    /// None when the client did not tell the connection used
+    reused: Option<bool>,
+    info: Option<ConnectionInfo>,
+}
+
+impl ConnectionUse {
+    /// Phases of a request taking `request_time_ms` until its headers arrived. Those of
+    /// the connection only count when the request opened it
+    fn timings(&self, request_time_ms: u64) -> Timings {
+        let Some(info) = self.info.as_ref().filter(|_| self.reused == Some(false)) else {
+            return Timings {
+                ttfb_ms: request_time_ms,
+                ..Default::default()
+            };
+        };
+
+        let as_ms = |time: Duration| time.as_millis() as u64;
+        let dns_ms = info.dns_time.map(as_ms);
+        let connect_ms = as_ms(info.connect_time);
+        let tls_ms = info.tls_time.map(as_ms);
+        let connection_ms = dns_ms.unwrap_or_default() + connect_ms + tls_ms.unwrap_or_default();
+
+        Timings {
+            dns_ms,
+            connect_ms: Some(connect_ms),
+            tls_ms,
+            ttfb_ms: request_time_ms.saturating_sub(connection_ms),
+            download_ms: 0,
+        }
+    }
+}
+
+/// Builds the request to be sent after a redirect response, following the same rules
+/// of browsers: 303 (and 301/302 after a POST) changes the method to GET, dropping the body.
+/// Credentials are not forwarded to other hosts.
+fn redirect_request(
+    mut request: HttpRequest,
+    response: &hyper::Response<Body>,
+) -> Option<anyhow::Result<HttpRequest>> {
+    let status = response.status();
+    let location = response.headers().get(LOCATION)?.to_str().ok()?;
+
+    let next_url = match request.url.join(location) {
+        Ok(url) => url,
+        Err(err) => return Some(Err(err.into())),
+    };
+
+    let changes_to_get = match status {
+        StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND => request.method == Method::POST,
+        StatusCode::SEE_OTHER => request.method != Method::HEAD,
+        StatusCode::TEMPORARY_REDIRECT | StatusCode::PERMANENT_REDIRECT => false,
+        _ => return None,
+    };
+
+    if changes_to_get {
+        request.method = Method::GET;
+        request.body = RequestBody::Empty;
+        for header in [CONTENT_TYPE, CONTENT_LENGTH, TRANSFER_ENCODING] {
+            request.headers.remove(header);
+        }
+    }
+
+    let is_same_origin = request.url.host_str() == next_url.host_str()
+        && request.url.port_or_known_default() == next_url.port_or_known_default();
+    if !is_same_origin {
+        for header in [AUTHORIZATION, COOKIE, PROXY_AUTHORIZATION] {
+            request.headers.remove(header);
+        }
+    }
+
+    request.url = next_url;
+
+    Some(Ok(request))
+}
+
+/// Sends the cookies of jar matching the URL, after the ones given by the user
+fn add_jar_cookies(request: &mut HttpRequest, cookies: &CookieJar) -> anyhow::Result<()> {
+    let Some(jar_cookies) = cookies.header_for(&request.url) else {
+        return Ok(());
+    };
+
+    let value = match request.headers.get(COOKIE).and_then(|v| v.to_str().ok()) {
+        Some(given_cookies) => format!("{given_cookies}; {jar_cookies}"),
+        None => jar_cookies,
+    };
+    request
+        .headers
+        .insert(COOKIE, HeaderValue::from_str(&value)?);
+
+    Ok(())
+}
+
+type BodyPart = (BoxStream<'static, std::io::Result<Bytes>>, u64);
+
+/// Body of a 'multipart/form-data' request, along with its length. Files are opened
+/// before sending, so a missing one fails the request right away
+async fn multipart_body(fields: &[MultipartField], boundary: &str) -> anyhow::Result<BodyPart> {
+    let mut parts: Vec<BodyPart> = Vec::new();
+
+    for field in fields {
+        match field {
+            MultipartField::Text { name, value } => parts.push(text_part(format!(
+                "--{boundary}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{value}\r\n",
+                quoted(name)
+            ))),
+            MultipartField::File {
+                name,
+                path,
+                content_type,
+            } => {
+                let file = tokio::fs::File::open(path)
+                    .await
+                    .map_err(|err| Error::msg(format!("Could not open file '{path}': {err}")))?;
+                let length = file.metadata().await?.len();
+
+                let content_type = content_type.clone().unwrap_or_else(|| {
+                    mime_guess::from_path(path)
+                        .first_or_octet_stream()
+                        .to_string()
+                });
+                let file_name = Path::new(path)
+                    .file_name()
+                    .map(|file_name| {
+                        format!("; filename=\"{}\"", quoted(&file_name.to_string_lossy()))
+                    })
+                    .unwrap_or_default();
+
+                parts.push(text_part(format!(
+                    "--{boundary}\r\nContent-Disposition: form-data; name=\"{}\"{file_name}\r\n\
+                     Content-Type: {content_type}\r\n\r\n",
+                    quoted(name)
+                )));
+                parts.push((ReaderStream::new(file).boxed(), length));
+                parts.push(text_part("\r\n".to_string()));
+            }
+        }
+    }
+    parts.push(text_part(format!("--{boundary}--\r\n")));
+
+    let length = parts.iter().map(|(_, length)| length).sum();
+    let parts = parts.into_iter().map(|(part, _)| part);
+
+    Ok((stream::iter(parts).flatten().boxed(), length))
+}
+
+/// Body streaming the given chunks, which notifies `uploaded` once the connection asks
+/// for more after the last one
+fn body_notifying_end(
+    chunks: BoxStream<'static, std::io::Result<Bytes>>,
+    uploaded: Arc<Notify>,
+) -> Body {
+    let end = stream::poll_fn(move |_| {
+        uploaded.notify_one();
+        Poll::Ready(None)
+    });
+    Body::wrap_stream(chunks.chain(end))
+}
+
+fn text_part(text: String) -> BodyPart {
+    let length = text.len() as u64;
+    (
+        stream::once(async { Ok(Bytes::from(text)) }).boxed(),
+        length,
+    )
+}
+
+/// Value escaped to be put between the quotes of a 'Content-Disposition' parameter
+fn quoted(value: &str) -> String {
+    value
+        .replace('"', "%22")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+/// Decoder of the body of response, when it should be decoded and its encoding is known
+fn decoder_of_response(
+    response: &hyper::Response<Body>,
+    decompress: bool,
+) -> Option<ContentDecoder> {
+    let content_encoding = response.headers().get(CONTENT_ENCODING)?.to_str().ok()?;
+    decompress
+        .then(|| ContentDecoder::for_content_encoding(content_encoding))
+        .flatten()
+}
+
+/// Next chunk of the body of response, None once all of it was received
+async fn next_chunk(response: &mut hyper::Response<Body>) -> hyper::Result<Option<Bytes>> {
+    response.body_mut().data().await.transpose()
+}
+
+/// Waits for the given read of the response, failing if the server takes longer than
+/// `read_timeout` to send anything
+async fn with_read_timeout<T>(
+    read_timeout: Option<Duration>,
+    read: impl Future<Output = hyper::Result<T>>,
+) -> anyhow::Result<T> {
+    match read_timeout {
+        Some(read_timeout) => Ok(tokio::time::timeout(read_timeout, read).await??),
+        None => Ok(read.await?),
+    }
+}
+
+/// Requests with AWS auth are signed at last, once their body files were read, as the
+/// signature covers their final data
+fn with_aws_signature(request: RequestData) -> anyhow::Result<RequestData> {
+    match &request.auth {
+        Some(auth) if auth.auth_type == AuthType::AwsSigV4 => {
+            AwsSigner::from_auth(auth)?.sign(request, unix_now())
+        }
+        _ => Ok(request),
+    }
+}
+
+/// Failure to validate the certificate of server, which rustls reports inside IO errors
+fn certificate_error(err: &anyhow::Error) -> Option<&rustls::Error> {
+    err.chain()
+        .find_map(|err| {
+            // IO errors do not expose the error they wrap as its `source`
+            let mut err: &(dyn std::error::Error + 'static) = err;
+            while let Some(io_err) = err.downcast_ref::<std::io::Error>() {
+                err = io_err.get_ref()?;
+            }
+            err.downcast_ref::<rustls::Error>()
+        })
+        .filter(|err| matches!(err, rustls::Error::InvalidCertificate(_)))
+}
+
+/// Error of a request which must be sent as HTTP/2 over an HTTP/1 connection
+fn is_unsupported_version_error(err: &anyhow::Error) -> bool {
+    err.chain().any(|err| err.is::<Http2NotAgreed>())
+}
+
+/// Failure which may not happen on a next attempt, as a refused or reset connection
+fn is_transient_error(err: &anyhow::Error) -> bool {
+    if is_unsupported_version_error(err) {
+        return false;
+    }
+
+    err.chain().any(|err| {
+        if let Some(err) = err.downcast_ref::<hyper::Error>() {
+            err.is_connect() || err.is_incomplete_message() || err.is_closed()
+        } else if let Some(err) = err.downcast_ref::<std::io::Error>() {
+            matches!(
+                err.kind(),
+                std::io::ErrorKind::ConnectionReset
+                    | std::io::ErrorKind::ConnectionAborted
+                    | std::io::ErrorKind::BrokenPipe
+                    | std::io::ErrorKind::UnexpectedEof
+            )
+        } else {
+            false
+        }
+    })
+}
+
+/// Timeout of the whole request, of a read, or of a connect which the client gives
+/// as the cause of its error
+fn is_timeout_error(err: &anyhow::Error) -> bool {
+    err.chain()
+        .any(|err| err.is::<tokio::time::error::Elapsed>())
+}
+
+/// Connections requests were sent over, to tell the ones reused. The connector gives
+/// each connection it opens an id, which the client passes along with the responses
+/// received on it
+#[derive(Default)]
+struct ConnectionsTracker {
+    used_connections: Mutex<HashSet<u64>>,
+}
+
+impl ConnectionsTracker {
+    /// Returns whether the connection had already been used before
+    fn mark_as_used(&self, connection_id: u64) -> bool {
+        let mut used_connections = self.used_connections.lock().unwrap();
+        !used_connections.insert(connection_id)
+    }
+}
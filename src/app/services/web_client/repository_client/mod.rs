@@ -1,10 +1,13 @@
-pub mod reqwest;
+mod connector;
+pub mod hyper_client;
 
 use mockall::automock;
 use mockall::predicate::*;
+use tokio::sync::mpsc::UnboundedSender;
 use tokio::task::JoinHandle as TokioTask;
 
-use super::entities::Response;
+use super::downloads::DownloadTarget;
+use super::entities::{FailedAttempt, Response};
 use crate::app::services::request::entities::requests::RequestData;
 
 pub type TaskRunningRequest = TokioTask<anyhow::Result<Response>>;
@@ -18,4 +21,14 @@ pub type TaskRunningRequest = TokioTask<anyhow::Result<Response>>;
 #[automock]
 pub trait HttpClientRepository: Send {
     fn submit_request(&self, request: RequestData) -> TaskRunningRequest;
+
+    /// Same as `submit_request`, but the body of a successful response is streamed to a file
+    fn download_request(&self, request: RequestData, target: DownloadTarget) -> TaskRunningRequest;
+
+    /// Same repository, but each failed attempt of its requests is sent to `attempts`
+    /// as soon as it fails, before the request is sent again
+    fn reporting_attempts(
+        &self,
+        attempts: UnboundedSender<FailedAttempt>,
+    ) -> Box<dyn HttpClientRepository>;
 }
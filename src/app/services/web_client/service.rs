@@ -1,3 +1,7 @@
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::downloads::DownloadTarget;
+use super::entities::FailedAttempt;
 use super::facade::WebClientFacade;
 use super::repository_client::{HttpClientRepository, TaskRunningRequest};
 use crate::app::services::request::entities::requests::RequestData;
@@ -18,11 +22,41 @@ impl WebClient {
 }
 
 impl WebClientFacade for WebClient {
-    fn submit_async(&mut self, mut request: RequestData) -> TaskRunningRequest {
-        if let Url::ValidatedUrl(url) = &mut request.url {
-            url.protocol.get_or_insert("http".to_string());
+    fn submit_async(
+        &mut self,
+        request: RequestData,
+        attempts: Option<UnboundedSender<FailedAttempt>>,
+    ) -> TaskRunningRequest {
+        let request = with_default_protocol(request);
+        match attempts {
+            Some(attempts) => self
+                .http_client
+                .reporting_attempts(attempts)
+                .submit_request(request),
+            None => self.http_client.submit_request(request),
+        }
+    }
+
+    fn download_async(
+        &mut self,
+        request: RequestData,
+        target: DownloadTarget,
+        attempts: Option<UnboundedSender<FailedAttempt>>,
+    ) -> TaskRunningRequest {
+        let request = with_default_protocol(request);
+        match attempts {
+            Some(attempts) => self
+                .http_client
+                .reporting_attempts(attempts)
+                .download_request(request, target),
+            None => self.http_client.download_request(request, target),
         }
+    }
+}
 
-        self.http_client.submit_request(request)
+fn with_default_protocol(mut request: RequestData) -> RequestData {
+    if let Url::ValidatedUrl(url) = &mut request.url {
+        url.protocol.get_or_insert("http".to_string());
     }
+    request
 }
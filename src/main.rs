@@ -1,11 +1,17 @@
+use std::io::IsTerminal;
+
 use anyhow::Error;
 use directories::ProjectDirs;
 use treq::app::backend::AppBackend;
+use treq::app::config::{AppConfig, CONFIG_FILE};
+use treq::app::services::auth::service::AuthService;
+use treq::app::services::files::facade::FileServiceFacade;
 use treq::app::services::files::service::FileService;
 use treq::app::services::request::service::RequestService;
-use treq::app::services::web_client::repository_client::reqwest::ReqwestClientRepository;
+use treq::app::services::web_client::repository_client::hyper_client::HyperClientRepository;
 use treq::app::services::web_client::service::WebClient;
-use treq::utils::errors::print_pretty_error;
+use treq::utils::errors::{print_pretty_error, RequestCancelled, EXIT_CODE_CANCELLED};
+use treq::utils::files as file_utils;
 use treq::view::input::cli_definition::root_command;
 use treq::view::input::cli_input::CliInput;
 use treq::view::input_to_commands::map_input_to_commands;
@@ -15,7 +21,10 @@ const APP_AUTHOR: &str = env!("CARGO_PKG_AUTHORS");
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    runner().await.map_err(print_pretty_error)
+    match runner().await {
+        Err(err) if err.is::<RequestCancelled>() => std::process::exit(EXIT_CODE_CANCELLED),
+        result => result.map_err(print_pretty_error),
+    }
 }
 
 async fn runner() -> anyhow::Result<()> {
@@ -36,24 +45,38 @@ async fn runner() -> anyhow::Result<()> {
     // Cli Input
     // ----------------------------
     let args = root_command().get_matches();
-    let cli_inputs = CliInput::from_clap_matches(&args)?;
+    let stdin = std::io::stdin();
+    let piped_stdin = (!stdin.is_terminal()).then_some(stdin);
+    let cli_inputs = CliInput::from_clap_matches(&args)?.with_stdin_body(piped_stdin)?;
     let cli_commands = map_input_to_commands(cli_inputs)?;
     let commands_executors = cli_commands.into_iter().map(|choice| choice.get_executor());
 
     // ----------------------------
     //  BACKEND
     // ----------------------------
-    let req = RequestService::init();
-    let web = WebClient::init(ReqwestClientRepository);
     let files = FileService::init(config_dir, data_dir, tempfiles_dir);
-    let mut backend = AppBackend::init(req, web, files);
+    let config = {
+        let config_file = files.get_or_create_config_file(CONFIG_FILE.into())?;
+        AppConfig::from_json(&file_utils::read_from_file(config_file).await?)?
+    };
+
+    let req = RequestService::init();
+    let http_client = HyperClientRepository::init(config.http_client_config()?)?;
+    let web = WebClient::init(http_client.clone());
+    let auth = AuthService::init(config.oauth2.clone(), http_client);
+    let mut backend = AppBackend::init(req, web, files, auth);
 
     // ----------------------------
     //  Execute commands
     // ----------------------------
-    for executor in commands_executors {
-        executor.execute(&mut backend).await?;
+    let result = async {
+        for executor in commands_executors {
+            executor.execute(&mut backend).await?;
+        }
+        anyhow::Ok(())
     }
+    .await;
 
-    Ok(())
+    backend.close().await;
+    result
 }
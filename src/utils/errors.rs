@@ -2,6 +2,14 @@ use std::error::Error;
 
 use crossterm::style::{Color, SetForegroundColor};
 
+/// Exit code of a request cancelled by Ctrl-C, the one shells use for SIGINT
+pub const EXIT_CODE_CANCELLED: i32 = 130;
+
+/// Request cancelled by the user, whose message was already shown
+#[derive(Debug, thiserror::Error)]
+#[error("Request cancelled")]
+pub struct RequestCancelled;
+
 pub fn print_pretty_error<E>(error: E) -> E
 where
     E: AsRef<dyn Error>,
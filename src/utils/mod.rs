@@ -1,5 +1,6 @@
-pub mod channels;
 pub mod errors;
 pub mod files;
 pub mod regexes;
+pub mod signals;
+pub mod time;
 pub mod uuid;
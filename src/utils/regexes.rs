@@ -9,6 +9,7 @@ pub fn complete_url() -> &'static Regex {
             &[
                 r"^",
                 r"((?<protocol>https?):\/\/)?",
+                r"((?<username>[^:@\/\s?#]+)(:(?<password>[^@\/\s?#]*))?@)?",
                 r"(?<host>[a-zA-Z0-9._@+=-]+)",
                 r"(\:(?<port>[0-9]{1,6}))?",
                 r"(\/(?<routes>[a-zA-Z0-9._@=+\/-]+))?",
@@ -57,6 +58,26 @@ pub mod request_items {
             .get_or_init(|| Regex::new(r"^(?<key>[ -~]+):=(?<value>[ -~]+)$").unwrap())
     }
 
+    static BODY_FILE_VALUE_REGEX: OnceLock<Regex> = OnceLock::new();
+    pub fn body_file_value() -> &'static Regex {
+        BODY_FILE_VALUE_REGEX
+            .get_or_init(|| Regex::new(r"^(?<key>[ -~&&[^=:@]]+)=@(?<path>.+)$").unwrap())
+    }
+
+    static NON_STRING_BODY_FILE_VALUE_REGEX: OnceLock<Regex> = OnceLock::new();
+    pub fn non_string_body_file_value() -> &'static Regex {
+        NON_STRING_BODY_FILE_VALUE_REGEX
+            .get_or_init(|| Regex::new(r"^(?<key>[ -~&&[^=:@]]+):=@(?<path>.+)$").unwrap())
+    }
+
+    static FILE_VALUE_REGEX: OnceLock<Regex> = OnceLock::new();
+    pub fn file_value() -> &'static Regex {
+        FILE_VALUE_REGEX.get_or_init(|| {
+            Regex::new(r"^(?<key>[ -~&&[^=:@]]+)@(?<path>[^;]+)(;type=(?<content_type>[ -~]+))?$")
+                .unwrap()
+        })
+    }
+
     static ENCLOSED_BY_SINGLE_QUOTE_VALUE_REGEX: OnceLock<Regex> = OnceLock::new();
     pub fn enclosed_by_single_quote_value() -> &'static Regex {
         ENCLOSED_BY_SINGLE_QUOTE_VALUE_REGEX
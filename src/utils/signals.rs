@@ -0,0 +1,53 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Once;
+
+use tokio::sync::Notify;
+
+use super::errors::EXIT_CODE_CANCELLED;
+
+static LISTEN_INTERRUPTS: Once = Once::new();
+static CANCELLATION: Notify = Notify::const_new();
+static WAITING: AtomicUsize = AtomicUsize::new(0);
+
+/// Waits a Ctrl-C cancelling the request in progress.
+///
+/// Listening SIGINT replaces its default handling for the rest of the process, so it is
+/// listened only once, and a Ctrl-C received while nothing is waiting for it exits right
+/// away, as it would without the listener
+pub async fn request_cancelled() {
+    LISTEN_INTERRUPTS.call_once(|| {
+        tokio::spawn(listen_interrupts());
+    });
+
+    let notified = CANCELLATION.notified();
+    tokio::pin!(notified);
+    notified.as_mut().enable();
+
+    let _waiting = Waiting::start();
+    notified.await;
+}
+
+async fn listen_interrupts() {
+    while tokio::signal::ctrl_c().await.is_ok() {
+        if WAITING.load(Ordering::SeqCst) == 0 {
+            std::process::exit(EXIT_CODE_CANCELLED);
+        }
+        CANCELLATION.notify_waiters();
+    }
+}
+
+/// Marks a wait for cancellation in progress, until dropped
+struct Waiting;
+
+impl Waiting {
+    fn start() -> Self {
+        WAITING.fetch_add(1, Ordering::SeqCst);
+        Self
+    }
+}
+
+impl Drop for Waiting {
+    fn drop(&mut self) {
+        WAITING.fetch_sub(1, Ordering::SeqCst);
+    }
+}
@@ -4,7 +4,7 @@ use async_trait::async_trait;
 
 use super::ViewCommand;
 use crate::app::backend::Backend;
-use crate::view::output::utils::BREAK_LINE;
+use crate::view::output::utils::{BREAK_LINE, TAB_SPACE};
 use crate::view::output::writer::{CliWriterRepository, CrosstermCliWriter};
 use crate::view::style::{Color, StyledStr};
 
@@ -38,6 +38,23 @@ impl<Writer: CliWriterRepository> ViewCommand for InspectRequestExecutor<Writer>
         self.writer.print_lines([output]);
         self.writer.print_lines([BREAK_LINE]);
 
+        let default_headers = request_data.default_headers();
+        if !default_headers.is_empty() {
+            self.writer
+                .print_lines([" Default headers added on submit"]);
+            self.writer
+                .print_lines_styled(default_headers.iter().map(|(key, value)| {
+                    [
+                        StyledStr::from(TAB_SPACE),
+                        StyledStr::from("| "),
+                        StyledStr::from(key),
+                        StyledStr::from(":"),
+                        StyledStr::from(value),
+                    ]
+                }));
+            self.writer.print_lines([BREAK_LINE]);
+        }
+
         Ok(())
     }
 }
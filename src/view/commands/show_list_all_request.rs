@@ -13,6 +13,7 @@ pub struct ShowListAllRequestExecutor<Writer: CliWriterRepository> {
 }
 
 impl ShowListAllRequestExecutor<CrosstermCliWriter> {
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
         ShowListAllRequestExecutor {
             writer: CrosstermCliWriter::from(Box::new(stdout())),
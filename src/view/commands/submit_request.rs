@@ -1,13 +1,20 @@
-use std::io::{empty, stderr, stdout};
+use std::io::{empty, stderr, stdout, IsTerminal};
+use std::path::PathBuf;
 
 use async_trait::async_trait;
-use indicatif::{ProgressBar, ProgressStyle};
+use indicatif::{ProgressBar, ProgressFinish, ProgressStyle};
+use tokio::sync::mpsc::{self, UnboundedReceiver};
 
 use super::ViewCommand;
 use crate::app::backend::Backend;
 use crate::app::services::request::entities::requests::RequestData;
-use crate::app::services::web_client::entities::get_status_code_message;
-use crate::utils::channels::chain_listener_to_receiver;
+use crate::app::services::request::entities::sessions::Session;
+use crate::app::services::web_client::downloads::{DownloadProgress, DownloadTarget};
+use crate::app::services::web_client::entities::{
+    get_status_code_message, FailedAttempt, Response, ResponseStage,
+};
+use crate::utils::errors::RequestCancelled;
+use crate::utils::signals;
 use crate::view::input::cli_input::ViewOptions;
 use crate::view::output::utils::{BREAK_LINE, BREAK_LINE_WITH_GAP, SINGLE_SPACE, TAB_SPACE};
 use crate::view::output::writer::{CliWriterRepository, CrosstermCliWriter};
@@ -20,6 +27,7 @@ where
     W3: CliWriterRepository,
 {
     pub request: RequestData,
+    pub view_options: ViewOptions,
     pub writer_metadata: W1,
     pub writer_response: W2,
     pub writer_stderr: W3,
@@ -30,13 +38,16 @@ impl BasicRequestExecutor<CrosstermCliWriter, CrosstermCliWriter, CrosstermCliWr
         if view_options.print_body_only {
             BasicRequestExecutor {
                 request,
+                view_options: view_options.clone(),
                 writer_metadata: CrosstermCliWriter::from(empty()),
-                writer_response: CrosstermCliWriter::from(stdout()),
+                writer_response: CrosstermCliWriter::from(stdout())
+                    .with_is_terminal(stdout().is_terminal()),
                 writer_stderr: CrosstermCliWriter::from(stderr()),
             }
         } else if view_options.suppress_output {
             BasicRequestExecutor {
                 request,
+                view_options: view_options.clone(),
                 writer_metadata: CrosstermCliWriter::from(empty()),
                 writer_response: CrosstermCliWriter::from(empty()),
                 writer_stderr: CrosstermCliWriter::from(stderr()),
@@ -44,8 +55,10 @@ impl BasicRequestExecutor<CrosstermCliWriter, CrosstermCliWriter, CrosstermCliWr
         } else {
             BasicRequestExecutor {
                 request,
+                view_options: view_options.clone(),
                 writer_metadata: CrosstermCliWriter::from(stderr()),
-                writer_response: CrosstermCliWriter::from(stdout()),
+                writer_response: CrosstermCliWriter::from(stdout())
+                    .with_is_terminal(stdout().is_terminal()),
                 writer_stderr: CrosstermCliWriter::from(stderr()),
             }
         }
@@ -60,6 +73,16 @@ where
     W3: CliWriterRepository,
 {
     async fn execute(mut self: Box<Self>, provider: &mut dyn Backend) -> anyhow::Result<()> {
+        let session = match &self.view_options.session {
+            Some(session_options) => {
+                Some(provider.get_session(session_options.name.clone()).await?)
+            }
+            None => None,
+        };
+        if let Some(session) = &session {
+            self.request = session.apply_to(self.request.clone());
+        }
+
         let url = self.request.url.to_string();
 
         let title = {
@@ -75,9 +98,9 @@ where
             ]
         };
 
+        let headers_to_send = self.request.headers_with_defaults();
         let headers: Vec<[StyledStr; 5]> = {
-            self.request
-                .headers
+            headers_to_send
                 .iter()
                 .map(|(k, v)| {
                     [
@@ -94,35 +117,134 @@ where
         self.writer_metadata.print_lines([BREAK_LINE]);
         self.writer_metadata.print_lines_styled([title]);
         self.writer_metadata.print_lines_styled(headers);
-        self.writer_metadata.print_lines([BREAK_LINE]);
+        // The block of request is closed along with the response, once the
+        // connection used is known
+
+        let request_id = provider.add_request(self.request.clone()).await?;
+
+        // Failed attempts are shown as they happen, while waiting for the response
+        let (attempts_sender, mut attempts) = mpsc::unbounded_channel();
+        let mut failed_attempts = 0;
+
+        if let Some(download_options) = &self.view_options.download {
+            let (progress_sender, progress_receiver) = mpsc::unbounded_channel();
+            let mut target = DownloadTarget::default()
+                .with_resume(download_options.resume)
+                .with_progress(progress_sender);
+            if let Some(output) = &download_options.output {
+                target = target.with_path(PathBuf::from(output));
+            }
+
+            let started = tokio::time::Instant::now();
+            let response_submit = provider
+                .download_request_async(request_id, target, Some(attempts_sender))
+                .await?;
+            let response_to_show = tokio::select! {
+                response = async {
+                    show_download_progress(progress_receiver, attempts, &mut self.writer_metadata)
+                        .await;
+                    response_submit.await
+                } => response?,
+                _ = signals::request_cancelled() => Ok(cancelled_response(started)),
+            };
+            if let Some((name, session)) = self.session_to_save(session, &response_to_show) {
+                provider.save_session(name, session).await?;
+            }
+            return self.show_response(response_to_show);
+        }
 
-        let request_id = provider.add_request(self.request).await?;
-        let response_submit = provider.submit_request_async(request_id).await?;
-        let (response_submit, mut listener_submit) = chain_listener_to_receiver(response_submit);
+        let mut response_submit = provider
+            .submit_request_async(request_id, Some(attempts_sender))
+            .await?;
 
-        // Loading spinner
-        {
+        // Loading spinner, until the response or a Ctrl-C. Dropping the receiver of
+        // response aborts the request
+        let response_to_show = {
             let now = tokio::time::Instant::now();
 
             let pb = ProgressBar::new(100);
             pb.set_style(ProgressStyle::with_template("{spinner:.green} {msg}").unwrap());
             pb.set_message("Loading...\t\t 0 MS");
 
+            let cancel = signals::request_cancelled();
+            tokio::pin!(cancel);
+
             let mut intv = tokio::time::interval(std::time::Duration::from_millis(14));
-            loop {
-                if listener_submit.try_recv().is_ok() {
-                    break;
-                }
-                intv.tick().await;
-                pb.inc(1);
+            let response_to_show = loop {
+                tokio::select! {
+                    // Attempts are sent before the response, so all of them are shown
+                    biased;
+                    Some(attempt) = attempts.recv() => {
+                        failed_attempts += 1;
+                        pb.suspend(|| {
+                            show_failed_attempt(&mut self.writer_metadata, failed_attempts, &attempt)
+                        });
+                    }
+                    response = &mut response_submit => break response?,
+                    _ = &mut cancel => break Ok(cancelled_response(now)),
+                    _ = intv.tick() => {
+                        pb.inc(1);
 
-                let elapsed = format!(" {} MS ", now.elapsed().as_millis());
-                pb.set_message("Loading...\t\t".to_owned() + elapsed.as_str());
-            }
+                        let elapsed = format!(" {} MS ", now.elapsed().as_millis());
+                        pb.set_message("Loading...\t\t".to_owned() + elapsed.as_str());
+                    }
+                }
+            };
             pb.finish_and_clear();
+            response_to_show
+        };
+
+        if let Some((name, session)) = self.session_to_save(session, &response_to_show) {
+            provider.save_session(name, session).await?;
+        }
+        self.show_response(response_to_show)
+    }
+}
+
+impl<W1, W2, W3> BasicRequestExecutor<W1, W2, W3>
+where
+    W1: CliWriterRepository,
+    W2: CliWriterRepository,
+    W3: CliWriterRepository,
+{
+    /// Session updated by the response, with its name, unless it is read-only
+    fn session_to_save(
+        &self,
+        session: Option<Session>,
+        response: &anyhow::Result<Response>,
+    ) -> Option<(String, Session)> {
+        let session_options = self.view_options.session.as_ref()?;
+        let mut session = session.filter(|_| !session_options.read_only)?;
+        let response = response
+            .as_ref()
+            .ok()
+            .filter(|response| response.stage != ResponseStage::Cancelled)?;
+
+        session.update(&self.request, response.cookies.clone());
+        Some((session_options.name.clone(), session))
+    }
+
+    /// Ends the block of request with how it was sent, as told by its response
+    fn close_request_block(&mut self, response: Option<&Response>) {
+        if let Some(proxy) = response.and_then(|response| response.proxy.as_ref()) {
+            self.writer_metadata.print_lines_styled([[
+                StyledStr::from(TAB_SPACE),
+                StyledStr::from("PROXY: ").with_text_style(TextStyle::Bold),
+                StyledStr::from(proxy),
+            ]]);
         }
+        if let Some(remote_addr) = response.and_then(|response| response.remote_addr.as_ref()) {
+            self.writer_metadata.print_lines_styled([[
+                StyledStr::from(TAB_SPACE),
+                StyledStr::from("CONNECTED TO: ").with_text_style(TextStyle::Bold),
+                StyledStr::from(remote_addr),
+            ]]);
+        }
+        self.writer_metadata.print_lines([BREAK_LINE]);
+    }
 
-        let response_to_show = response_submit.await?;
+    fn show_response(&mut self, response_to_show: anyhow::Result<Response>) -> anyhow::Result<()> {
+        self.close_request_block(response_to_show.as_ref().ok());
 
         if let Err(err_message) = response_to_show {
             self.writer_stderr.print_lines_styled([[
@@ -137,9 +259,70 @@ where
         }
 
         let response = response_to_show.unwrap();
-        let response_status = response.status.to_string();
+
+        if response.stage == ResponseStage::Cancelled {
+            let message = format!("Request cancelled after {} MS", response.response_time_ms);
+            self.writer_stderr.print_lines_styled([[
+                StyledStr::from(TAB_SPACE),
+                StyledStr::from(&message).with_color_text(Color::Red),
+            ]]);
+            self.writer_stderr.print_lines([BREAK_LINE]);
+
+            return Err(RequestCancelled.into());
+        }
+
+        if response.stage == ResponseStage::TimedOut {
+            let message = format!("Request timed out after {} MS", response.response_time_ms);
+            self.writer_stderr.print_lines_styled([[
+                StyledStr::from(TAB_SPACE),
+                StyledStr::from(&message).with_color_text(Color::Red),
+            ]]);
+            self.writer_stderr.print_lines([BREAK_LINE]);
+
+            return anyhow::Ok(());
+        }
+
+        if self.view_options.print_all_responses {
+            for redirect in response.redirect_history.iter() {
+                let status = format!(
+                    "{} ({})",
+                    redirect.status,
+                    get_status_code_message(redirect.status)
+                );
+                let time = format!(" {} MS ", redirect.response_time_ms);
+                let location = redirect.header("location").unwrap_or_default();
+
+                self.writer_metadata.print_lines_styled([
+                    vec![
+                        StyledStr::from(TAB_SPACE),
+                        StyledStr::from("REDIRECT: ").with_text_style(TextStyle::Bold),
+                        StyledStr::from(&status),
+                        StyledStr::from("    "),
+                        StyledStr::from(&time),
+                    ],
+                    vec![
+                        StyledStr::from(TAB_SPACE),
+                        StyledStr::from("| "),
+                        StyledStr::from("location"),
+                        StyledStr::from(":"),
+                        StyledStr::from(location),
+                    ],
+                ]);
+                self.writer_metadata.print_lines([BREAK_LINE_WITH_GAP]);
+            }
+        }
+
+        let response_status = match &response.version {
+            Some(version) => format!("{version} {}", response.status),
+            None => response.status.to_string(),
+        };
 
         let response_time = format!(" {} MS ", response.response_time_ms);
+        let connection_info = match response.reused_connection {
+            Some(true) => "(reused connection)",
+            Some(false) => "(new connection)",
+            None => "",
+        };
 
         let response_status_message = get_status_code_message(response.status);
         let response_status_message_styled = format!(" ({response_status_message})");
@@ -151,6 +334,7 @@ where
             StyledStr::from(&response_status_message_styled),
             StyledStr::from("    "),
             StyledStr::from(&response_time),
+            StyledStr::from(connection_info),
         ];
         let headers: Vec<[StyledStr; 5]> = {
             response
@@ -169,11 +353,177 @@ where
         };
 
         self.writer_metadata.print_lines_styled([title_status]);
+        if self.view_options.print_timings {
+            let timings = &response.timings;
+            let mut phases = Vec::new();
+            if let Some(dns_ms) = timings.dns_ms {
+                phases.push(format!("DNS {dns_ms} MS"));
+            }
+            if let Some(connect_ms) = timings.connect_ms {
+                phases.push(format!("CONNECT {connect_ms} MS"));
+            }
+            if let Some(tls_ms) = timings.tls_ms {
+                phases.push(format!("TLS {tls_ms} MS"));
+            }
+            phases.push(format!("TTFB {} MS", timings.ttfb_ms));
+            phases.push(format!("DOWNLOAD {} MS", timings.download_ms));
+
+            self.writer_metadata.print_lines_styled([[
+                StyledStr::from(TAB_SPACE),
+                StyledStr::from("TIMINGS: ").with_text_style(TextStyle::Bold),
+                StyledStr::from(&phases.join(" | ")),
+            ]]);
+        }
         self.writer_metadata.print_lines([BREAK_LINE_WITH_GAP]);
         self.writer_metadata.print_lines_styled(headers);
         self.writer_metadata.print_lines([BREAK_LINE_WITH_GAP]);
-        self.writer_response.print_lines([response.body]);
+
+        // Output meant for scripts has no metadata, so timings are given apart to be parsed
+        let shows_metadata =
+            !self.view_options.print_body_only && !self.view_options.suppress_output;
+        if self.view_options.print_timings && !shows_metadata {
+            self.writer_stderr
+                .print_lines([serde_json::to_string(&response.timings)?]);
+        }
+
+        if let Some(wire_size) = response.wire_size {
+            let size = format!(
+                "{} bytes decoded, {wire_size} bytes on wire ({})",
+                response.body_size(),
+                response.header("content-encoding").unwrap_or_default()
+            );
+            self.writer_metadata.print_lines_styled([[
+                StyledStr::from(TAB_SPACE),
+                StyledStr::from("SIZE: ").with_text_style(TextStyle::Bold),
+                StyledStr::from(&size),
+            ]]);
+            self.writer_metadata.print_lines([BREAK_LINE_WITH_GAP]);
+        }
+
+        if let Some(download) = &response.download {
+            let message = if download.resumed_from > 0 {
+                format!(
+                    "Saved to '{}' ({} bytes, resumed after {} bytes)",
+                    download.path.display(),
+                    download.bytes_written,
+                    download.resumed_from
+                )
+            } else {
+                format!(
+                    "Saved to '{}' ({} bytes)",
+                    download.path.display(),
+                    download.bytes_written
+                )
+            };
+            self.writer_stderr.print_lines_styled([[
+                StyledStr::from(TAB_SPACE),
+                StyledStr::from(&message).with_color_text(Color::Blue),
+            ]]);
+        } else if !response.is_binary() {
+            self.writer_response.print_lines([response.body]);
+        } else if !self.writer_response.is_terminal() {
+            self.writer_response.print_bytes(&response.body_bytes);
+        } else {
+            // Dumping binary content would only mess up the terminal
+            let message = format!(
+                "Binary response body not shown ({} bytes, {}). Redirect output to a file to save it, like '> file'",
+                response.body_bytes.len(),
+                response.content_type().unwrap_or("unknown content type"),
+            );
+            self.writer_stderr.print_lines_styled([[
+                StyledStr::from(TAB_SPACE),
+                StyledStr::from(&message).with_color_text(Color::Yellow),
+            ]]);
+        }
 
         Ok(())
     }
 }
+
+/// Response recorded when the request is cancelled by Ctrl-C
+fn cancelled_response(started: tokio::time::Instant) -> Response {
+    Response {
+        stage: ResponseStage::Cancelled,
+        response_time_ms: started.elapsed().as_millis() as u64,
+        ..Default::default()
+    }
+}
+
+/// Attempt of the request that failed, shown before it is sent again
+fn show_failed_attempt(
+    writer: &mut impl CliWriterRepository,
+    number: usize,
+    attempt: &FailedAttempt,
+) {
+    let outcome = match (attempt.status, &attempt.error) {
+        (Some(status), _) => format!("{status} ({})", get_status_code_message(status)),
+        (None, error) => error.clone().unwrap_or_default(),
+    };
+    let title = format!("ATTEMPT {number}: ");
+    let outcome = format!("{outcome}    retrying in {} MS", attempt.wait_ms);
+
+    writer.print_lines_styled([[
+        StyledStr::from(TAB_SPACE),
+        StyledStr::from(&title).with_text_style(TextStyle::Bold),
+        StyledStr::from(&outcome),
+    ]]);
+}
+
+/// Progress bar of a download, shown until the download finishes, along with the
+/// failed attempts of it
+async fn show_download_progress(
+    mut progress: UnboundedReceiver<DownloadProgress>,
+    mut attempts: UnboundedReceiver<FailedAttempt>,
+    writer: &mut impl CliWriterRepository,
+) {
+    let pb = ProgressBar::new_spinner().with_finish(ProgressFinish::AndClear);
+    pb.set_style(ProgressStyle::with_template("{spinner:.green} {msg}").unwrap());
+    pb.set_message("Waiting response...");
+    pb.enable_steady_tick(std::time::Duration::from_millis(80));
+
+    let mut failed_attempts = 0;
+    loop {
+        let event = tokio::select! {
+            biased;
+            Some(attempt) = attempts.recv() => {
+                failed_attempts += 1;
+                pb.suspend(|| show_failed_attempt(writer, failed_attempts, &attempt));
+                continue;
+            }
+            event = progress.recv() => event,
+        };
+        let Some(event) = event else {
+            break;
+        };
+
+        match event {
+            DownloadProgress::Started {
+                total_bytes: Some(total_bytes),
+                resumed_from,
+            } => {
+                pb.set_style(
+                    ProgressStyle::with_template(
+                        "{spinner:.green} [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({bytes_per_sec}, {eta})",
+                    )
+                    .unwrap()
+                    .progress_chars("=> "),
+                );
+                pb.set_length(total_bytes);
+                pb.set_position(resumed_from);
+            }
+            DownloadProgress::Started {
+                total_bytes: None,
+                resumed_from,
+            } => {
+                pb.set_style(
+                    ProgressStyle::with_template("{spinner:.green} {bytes} ({bytes_per_sec})")
+                        .unwrap(),
+                );
+                pb.set_position(resumed_from);
+            }
+            DownloadProgress::Received(bytes) => pb.inc(bytes),
+        }
+    }
+
+    pb.finish_and_clear();
+}
@@ -1,4 +1,4 @@
-use std::io::{empty, stderr, stdout};
+use std::io::{empty, stderr, stdout, IsTerminal};
 
 use async_trait::async_trait;
 
@@ -19,6 +19,7 @@ where
 {
     pub request_name: String,
     pub input_request_data: PartialRequestData,
+    pub view_options: ViewOptions,
     pub writer_metadata: W1,
     pub writer_response: W2,
     pub writer_stderr: W3,
@@ -34,14 +35,17 @@ impl SubmitSavedRequestExecutor<CrosstermCliWriter, CrosstermCliWriter, Crosster
             SubmitSavedRequestExecutor {
                 request_name,
                 input_request_data,
+                view_options: view_options.clone(),
                 writer_metadata: CrosstermCliWriter::from(empty()),
-                writer_response: CrosstermCliWriter::from(stdout()),
+                writer_response: CrosstermCliWriter::from(stdout())
+                    .with_is_terminal(stdout().is_terminal()),
                 writer_stderr: CrosstermCliWriter::from(stderr()),
             }
         } else if view_options.suppress_output {
             SubmitSavedRequestExecutor {
                 request_name,
                 input_request_data,
+                view_options: view_options.clone(),
                 writer_metadata: CrosstermCliWriter::from(empty()),
                 writer_response: CrosstermCliWriter::from(empty()),
                 writer_stderr: CrosstermCliWriter::from(stderr()),
@@ -50,8 +54,10 @@ impl SubmitSavedRequestExecutor<CrosstermCliWriter, CrosstermCliWriter, Crosster
             SubmitSavedRequestExecutor {
                 request_name,
                 input_request_data,
+                view_options: view_options.clone(),
                 writer_metadata: CrosstermCliWriter::from(stderr()),
-                writer_response: CrosstermCliWriter::from(stdout()),
+                writer_response: CrosstermCliWriter::from(stdout())
+                    .with_is_terminal(stdout().is_terminal()),
                 writer_stderr: CrosstermCliWriter::from(stderr()),
             }
         }
@@ -83,6 +89,7 @@ where
 
         Box::new(BasicRequestExecutor {
             request,
+            view_options: self.view_options,
             writer_metadata: self.writer_metadata,
             writer_response: self.writer_response,
             writer_stderr: self.writer_stderr,
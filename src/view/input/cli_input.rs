@@ -1,10 +1,20 @@
+use std::io::Read;
 use std::str::FromStr;
 
 use anyhow::{Error, Result};
 use clap::ArgMatches;
 use serde::Serialize;
 
-use crate::app::services::request::entities::methods::METHODS;
+use crate::app::services::request::entities::auth::AuthType;
+use crate::app::services::request::entities::compression::BodyCompression;
+use crate::app::services::request::entities::dns::{DnsSettings, IpVersion};
+use crate::app::services::request::entities::http_version::HttpVersion;
+use crate::app::services::request::entities::methods::{METHODS, STANDARD_METHODS};
+use crate::app::services::request::entities::proxies::ProxySettings;
+use crate::app::services::request::entities::redirects::RedirectPolicy;
+use crate::app::services::request::entities::retries::RetryPolicy;
+use crate::app::services::request::entities::timeouts::{millis_from_secs, Timeouts};
+use crate::app::services::request::entities::tls::TlsSettings;
 
 pub struct CliInput {
     pub choice: CliCommandChoice,
@@ -132,9 +142,9 @@ impl CliInput {
                     view_options,
                 })
             }
-            "GET" | "POST" | "PUT" | "DELETE" | "HEAD" | "PATCH" => {
+            method if STANDARD_METHODS.contains(&method) => {
                 let url = clap_args_utils::get_input(matches)?.to_string();
-                let method = METHODS::from_str(subcommand)?;
+                let method = METHODS::from_str(method)?;
 
                 Ok(CliInput {
                     choice: CliCommandChoice::BasicRequest { method, url },
@@ -146,23 +156,157 @@ impl CliInput {
             _ => Err(Error::msg("No valid subcommand")),
         }
     }
+
+    /// Uses the piped stdin as raw body, like `treq POST example.com < payload.json`.
+    /// `stdin` must be None when it's a terminal
+    pub fn with_stdin_body(mut self, stdin: Option<impl Read>) -> Result<CliInput> {
+        let builds_request = matches!(
+            self.choice,
+            CliCommandChoice::DefaultBasicRequest { .. }
+                | CliCommandChoice::BasicRequest { .. }
+                | CliCommandChoice::Run { .. }
+                | CliCommandChoice::Edit { .. }
+        );
+
+        let Some(mut stdin) = stdin.filter(|_| builds_request && !self.request_input.ignore_stdin)
+        else {
+            return Ok(self);
+        };
+
+        let mut body = String::new();
+        stdin
+            .read_to_string(&mut body)
+            .map_err(|err| Error::msg(format!("Could not read request body from stdin: {err}")))?;
+
+        if body.is_empty() {
+            return Ok(self);
+        }
+
+        if self.request_input.raw_body.is_some() {
+            return Err(Error::msg(
+                "Request body from stdin can not be used with '--raw', use '--ignore-stdin' to ignore it",
+            ));
+        }
+
+        self.request_input.raw_body = Some(body);
+        self.request_input.raw_body_from_stdin = true;
+
+        Ok(self)
+    }
 }
 
 #[derive(Default)]
 pub struct RequestBuildingOptions {
     pub request_items: Vec<String>,
     pub raw_body: Option<String>,
+    /// Raw body was piped by stdin, not given by '--raw'
+    pub raw_body_from_stdin: bool,
+    pub ignore_stdin: bool,
+    pub form: bool,
+    pub keep_file_references: bool,
     pub url_manual: Option<String>,
     pub method_manual: Option<METHODS>,
+    pub timeouts: Timeouts,
+    pub redirects: RedirectPolicy,
+    pub retries: RetryPolicy,
+    pub tls: TlsSettings,
+    pub proxy: ProxySettings,
+    pub dns: DnsSettings,
+    pub auth: Option<String>,
+    pub auth_type: Option<AuthType>,
+    pub http_version: Option<HttpVersion>,
+    pub unix_socket: Option<String>,
+    pub compress: Option<BodyCompression>,
+    pub skip_decompression: bool,
+    pub skip_default_headers: bool,
 }
 impl RequestBuildingOptions {
     pub fn from_clap_matches(matches: &ArgMatches) -> Result<RequestBuildingOptions> {
         Ok(RequestBuildingOptions {
             request_items: clap_args_utils::get_many(matches, "request-items").unwrap_or_default(),
             raw_body: clap_args_utils::get_one(matches, "raw"),
+            raw_body_from_stdin: false,
+            ignore_stdin: clap_args_utils::get_one::<bool>(matches, "ignore-stdin")
+                .unwrap_or_default(),
+            form: clap_args_utils::get_one::<bool>(matches, "form").unwrap_or_default(),
+            keep_file_references: clap_args_utils::get_one::<bool>(matches, "keep-file-refs")
+                .unwrap_or_default(),
             url_manual: clap_args_utils::get_one(matches, "url-manual"),
             method_manual: clap_args_utils::get_one::<String>(matches, "method-manual")
-                .and_then(|m| METHODS::from_str(&m).ok()),
+                .map(|m| METHODS::from_str(&m))
+                .transpose()?,
+            timeouts: Timeouts {
+                total_ms: clap_args_utils::get_seconds_as_ms(matches, "timeout")?,
+                connect_ms: clap_args_utils::get_seconds_as_ms(matches, "connect-timeout")?,
+                read_ms: clap_args_utils::get_seconds_as_ms(matches, "read-timeout")?,
+            },
+            redirects: RedirectPolicy {
+                follow: clap_args_utils::get_flag_or_negation(matches, "follow", "no-follow"),
+                max_redirects: clap_args_utils::get_one(matches, "max-redirects"),
+            },
+            retries: RetryPolicy {
+                retries: clap_args_utils::get_one(matches, "retries"),
+                backoff_ms: clap_args_utils::get_seconds_as_ms(matches, "retry-backoff")?,
+                max_backoff_ms: clap_args_utils::get_seconds_as_ms(matches, "retry-max-backoff")?,
+                retry_on: matches
+                    .try_get_many::<u16>("retry-on")
+                    .ok()
+                    .flatten()
+                    .map(|statuses| statuses.copied().collect()),
+                all_methods: clap_args_utils::get_flag_or_negation(
+                    matches,
+                    "retry-all-methods",
+                    "no-retry-all-methods",
+                ),
+            },
+            tls: TlsSettings {
+                verify: clap_args_utils::get_one(matches, "verify"),
+                ca_bundle: clap_args_utils::get_one(matches, "ca-bundle"),
+                cert: clap_args_utils::get_one(matches, "cert"),
+                cert_key: clap_args_utils::get_one(matches, "cert-key"),
+            },
+            proxy: clap_args_utils::get_many(matches, "proxy")
+                .unwrap_or_default()
+                .iter()
+                .try_fold(
+                    ProxySettings {
+                        no_proxy: clap_args_utils::get_one(matches, "no-proxy"),
+                        ..Default::default()
+                    },
+                    |proxy, rule| proxy.with_rule(rule),
+                )?,
+            dns: clap_args_utils::get_many(matches, "resolve")
+                .unwrap_or_default()
+                .iter()
+                .try_fold(
+                    DnsSettings {
+                        ip_version: [("ipv4", IpVersion::V4), ("ipv6", IpVersion::V6)]
+                            .into_iter()
+                            .find(|(flag, _)| {
+                                clap_args_utils::get_one::<bool>(matches, flag).unwrap_or_default()
+                            })
+                            .map(|(_, ip_version)| ip_version),
+                        ..Default::default()
+                    },
+                    |dns, rule| dns.with_rule(rule),
+                )?,
+            auth: clap_args_utils::get_one(matches, "auth"),
+            auth_type: clap_args_utils::get_one::<String>(matches, "auth-type")
+                .map(|auth_type| AuthType::from_str(&auth_type))
+                .transpose()?,
+            http_version: ["http1.1", "http2", "http2-prior-knowledge"]
+                .into_iter()
+                .find(|flag| clap_args_utils::get_one::<bool>(matches, flag).unwrap_or_default())
+                .map(HttpVersion::from_str)
+                .transpose()?,
+            unix_socket: clap_args_utils::get_one(matches, "unix-socket"),
+            compress: clap_args_utils::get_one::<String>(matches, "compress")
+                .map(|compression| BodyCompression::from_str(&compression))
+                .transpose()?,
+            skip_decompression: clap_args_utils::get_one::<bool>(matches, "no-decompress")
+                .unwrap_or_default(),
+            skip_default_headers: clap_args_utils::get_one::<bool>(matches, "no-default-headers")
+                .unwrap_or_default(),
         })
     }
 }
@@ -183,6 +327,26 @@ impl SavingOptions {
 pub struct ViewOptions {
     pub print_body_only: bool,
     pub suppress_output: bool,
+    pub print_all_responses: bool,
+    pub print_timings: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub download: Option<DownloadOptions>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session: Option<SessionOptions>,
+}
+
+#[derive(Default, Debug, Eq, PartialEq, Serialize, Clone)]
+pub struct DownloadOptions {
+    /// File to save, otherwise its name is taken from the response
+    pub output: Option<String>,
+    pub resume: bool,
+}
+
+#[derive(Default, Debug, Eq, PartialEq, Serialize, Clone)]
+pub struct SessionOptions {
+    pub name: String,
+    /// Use the session without saving anything to it
+    pub read_only: bool,
 }
 
 impl ViewOptions {
@@ -192,10 +356,44 @@ impl ViewOptions {
                 .unwrap_or(false),
             suppress_output: clap_args_utils::get_one::<bool>(matches, "suppress-output")
                 .unwrap_or(false),
+            print_all_responses: clap_args_utils::get_one::<bool>(matches, "print-all-responses")
+                .unwrap_or(false),
+            print_timings: clap_args_utils::get_one::<bool>(matches, "timings").unwrap_or(false),
+            download: DownloadOptions::from_clap_matches(matches),
+            session: SessionOptions::from_clap_matches(matches),
         })
     }
 }
 
+impl DownloadOptions {
+    pub fn from_clap_matches(matches: &ArgMatches) -> Option<DownloadOptions> {
+        let output: Option<String> = clap_args_utils::get_one(matches, "output");
+        let download = clap_args_utils::get_one::<bool>(matches, "download").unwrap_or(false);
+
+        (download || output.is_some()).then(|| DownloadOptions {
+            output,
+            resume: clap_args_utils::get_one::<bool>(matches, "continue").unwrap_or(false),
+        })
+    }
+}
+
+impl SessionOptions {
+    pub fn from_clap_matches(matches: &ArgMatches) -> Option<SessionOptions> {
+        let session =
+            clap_args_utils::get_one::<String>(matches, "session").map(|name| SessionOptions {
+                name,
+                read_only: false,
+            });
+        let read_only_session = clap_args_utils::get_one::<String>(matches, "session-read-only")
+            .map(|name| SessionOptions {
+                name,
+                read_only: true,
+            });
+
+        session.or(read_only_session)
+    }
+}
+
 mod clap_args_utils {
     use std::any::Any;
 
@@ -216,6 +414,21 @@ mod clap_args_utils {
         args.try_get_one::<T>(name).ok().flatten().cloned()
     }
 
+    /// Value of a flag which can also be turned off by its negation, as '--follow'
+    /// and '--no-follow'. None when neither is given
+    pub fn get_flag_or_negation(args: &ArgMatches, name: &str, negation: &str) -> Option<bool> {
+        if clap_args_utils::get_one::<bool>(args, negation).unwrap_or_default() {
+            return Some(false);
+        }
+        clap_args_utils::get_one::<bool>(args, name).filter(|v| *v)
+    }
+
+    pub fn get_seconds_as_ms(args: &ArgMatches, name: &str) -> Result<Option<u64>> {
+        clap_args_utils::get_one::<f64>(args, name)
+            .map(millis_from_secs)
+            .transpose()
+    }
+
     pub fn get_many(args: &ArgMatches, name: &str) -> Option<Vec<String>> {
         Some(
             args.try_get_many::<String>(name)
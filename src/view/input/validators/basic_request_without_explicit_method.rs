@@ -7,11 +7,28 @@ use crate::view::input::cli_input::{CliCommandChoice, CliInput};
 pub fn validate_basic_request_without_explicit_method(mut input: CliInput) -> Result<CliInput> {
     if let CliCommandChoice::DefaultBasicRequest { ref url } = input.choice {
         let url = url.clone();
+
+        if let Some(method) = input.request_input.method_manual.clone() {
+            input.choice = CliCommandChoice::BasicRequest { method, url };
+            return Ok(input);
+        }
+
+        if input.request_input.raw_body_from_stdin {
+            input.choice = CliCommandChoice::BasicRequest {
+                method: METHODS::POST,
+                url,
+            };
+            return Ok(input);
+        }
+
         input
             .request_input
             .request_items
             .iter()
-            .any(|v| regexes::request_items::body_value().is_match(v))
+            .any(|v| {
+                regexes::request_items::body_value().is_match(v)
+                    || regexes::request_items::file_value().is_match(v)
+            })
             .then(|| {
                 input.choice = CliCommandChoice::BasicRequest {
                     method: METHODS::POST,
@@ -5,19 +5,31 @@ use crate::utils::regexes;
 use crate::view::input::cli_input::CliInput;
 
 pub fn validate_body_values_with_raw(input: CliInput) -> Result<CliInput> {
+    // A form raw value is parsed as fields, so it's always possible to add more
+    if input.request_input.form {
+        return Ok(input);
+    }
+
     if let Some(raw_value) = &input.request_input.raw_body {
+        // Content of a file is only known when it's read
+        if raw_value.starts_with('@') && !input.request_input.raw_body_from_stdin {
+            return Ok(input);
+        }
+
         let is_raw_input_a_valid_map =
             serde_json::from_str::<Map<String, Value>>(raw_value).is_ok();
 
         if !is_raw_input_a_valid_map {
-            let has_some_body_insert_in_request_items = input
-                .request_input
-                .request_items
-                .iter()
-                .any(|v| regexes::request_items::body_value().is_match(v));
+            let has_some_body_insert_in_request_items =
+                input.request_input.request_items.iter().any(|v| {
+                    regexes::request_items::body_value().is_match(v)
+                        || regexes::request_items::file_value().is_match(v)
+                });
 
             if has_some_body_insert_in_request_items {
-                return Err(Error::msg("raw body must be a valid JSON object"));
+                return Err(Error::msg(
+                    "raw body (from '--raw' or stdin) must be a valid JSON object to add body values",
+                ));
             }
         }
     }
@@ -0,0 +1,22 @@
+use anyhow::{Error, Result};
+
+use crate::utils::regexes;
+use crate::view::input::cli_input::CliInput;
+
+pub fn validate_form_without_non_string_values(input: CliInput) -> Result<CliInput> {
+    if input.request_input.form {
+        let has_some_non_string_value = input
+            .request_input
+            .request_items
+            .iter()
+            .any(|v| regexes::request_items::non_string_body_value().is_match(v));
+
+        if has_some_non_string_value {
+            return Err(Error::msg(
+                "non string values (':=') can not be used with '--form'",
+            ));
+        }
+    }
+
+    Ok(input)
+}
@@ -1,3 +1,4 @@
 pub mod basic_request_without_explicit_method;
 pub mod body_values_with_raw;
+pub mod form_values;
 pub mod url_alias;
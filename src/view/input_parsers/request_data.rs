@@ -3,9 +3,15 @@ use std::collections::HashMap;
 use anyhow::Result;
 use serde_json::Value;
 
+use crate::app::services::request::entities::auth::Auth;
+use crate::app::services::request::entities::body_files::{
+    merge_body_file_references, BodyFileReference,
+};
 use crate::app::services::request::entities::methods::METHODS;
+use crate::app::services::request::entities::multipart::{merge_multipart_fields, MultipartField};
 use crate::app::services::request::entities::partial_entities::PartialRequestData;
-use crate::app::services::request::entities::requests::BodyPayload;
+use crate::app::services::request::entities::requests::{multipart_text_fields, BodyPayload};
+use crate::app::services::request::entities::tls::TlsSettings;
 use crate::app::services::request::entities::url::{Url, UrlInfo};
 use crate::view::input::cli_input::{CliCommandChoice, CliInput, RequestBuildingOptions};
 
@@ -14,49 +20,160 @@ pub fn parse_inputs_to_request_data(input: &CliInput) -> Result<PartialRequestDa
     let base_request = {
         let RequestBuildingOptions {
             raw_body,
+            raw_body_from_stdin,
+            form,
             url_manual,
             method_manual,
+            timeouts,
+            redirects,
+            retries,
+            tls,
+            proxy,
+            dns,
+            http_version,
+            unix_socket,
+            compress,
+            skip_decompression,
+            skip_default_headers,
             ..
         } = &input.request_input;
 
-        let mut req = PartialRequestData::default();
-        req.method = *method_manual;
-        req.url = url_manual.as_ref().map(|value| Url::from_str(value));
-        req.body = raw_body.as_ref().map(|v| BodyPayload::from_str(v));
-        req
+        let raw_body_file = raw_body
+            .as_ref()
+            .filter(|_| !raw_body_from_stdin)
+            .and_then(|value| value.strip_prefix('@'));
+
+        PartialRequestData {
+            method: method_manual.clone(),
+            url: url_manual.as_ref().map(|value| Url::from_str(value)),
+            body: match raw_body {
+                // The file is read as form fields on form mode
+                Some(_) if raw_body_file.is_some() => form.then(|| BodyPayload::Form(Vec::new())),
+                Some(value) if *form => Some(BodyPayload::form_from_str(value)?),
+                Some(value) => Some(BodyPayload::from_str(value)),
+                None if *form => Some(BodyPayload::Form(Vec::new())),
+                None => None,
+            },
+            body_files: raw_body_file
+                .map(|path| -> Result<_> {
+                    Ok(vec![BodyFileReference {
+                        keys: Vec::new(),
+                        path: absolute_path(path)?,
+                        as_json: false,
+                    }])
+                })
+                .transpose()?,
+            timeouts: (!timeouts.is_empty()).then_some(*timeouts),
+            redirects: (!redirects.is_empty()).then_some(*redirects),
+            retries: (!retries.is_empty()).then(|| retries.clone()),
+            // Saved requests keep working from any directory
+            tls: (!tls.is_empty())
+                .then(|| -> Result<_> {
+                    let absolute =
+                        |path: &Option<String>| path.as_deref().map(absolute_path).transpose();
+                    Ok(TlsSettings {
+                        verify: tls.verify,
+                        ca_bundle: absolute(&tls.ca_bundle)?,
+                        cert: absolute(&tls.cert)?,
+                        cert_key: absolute(&tls.cert_key)?,
+                    })
+                })
+                .transpose()?,
+            proxy: (!proxy.is_empty()).then(|| proxy.clone()),
+            dns: (!dns.is_empty()).then(|| dns.clone()),
+            http_version: *http_version,
+            unix_socket: unix_socket.as_deref().map(absolute_path).transpose()?,
+            compress: *compress,
+            skip_decompression: skip_decompression.then_some(true),
+            skip_default_headers: skip_default_headers.then_some(true),
+            ..Default::default()
+        }
     };
 
     // Request data from 'CliCommandChoice'
     let base_request = match input.choice {
-        CliCommandChoice::BasicRequest { method, ref url } => {
-            base_request.with_method(method).with_url(url)
-        }
+        CliCommandChoice::BasicRequest {
+            ref method,
+            ref url,
+        } => base_request.with_method(method.clone()).with_url(url),
         CliCommandChoice::DefaultBasicRequest { ref url } => {
             base_request.with_method(METHODS::GET).with_url(url)
         }
         _ => base_request,
     };
+    let base_request = with_auth_of_input(base_request, &input.request_input)?;
 
     // From request items
-    let base_request =
-        input
-            .request_input
-            .request_items
-            .iter()
-            .fold(base_request, |req_data, item| {
-                [
-                    parsers_request_items::operators::query_param_value,
-                    parsers_request_items::operators::non_string_body_value,
-                    parsers_request_items::operators::body_value,
-                    parsers_request_items::operators::header_value,
-                ]
-                .into_iter()
-                .find_map(|parser| parser(item.as_ref(), &req_data))
-                .and_then(Result::ok)
-                .unwrap_or(req_data)
-            });
-
-    Ok(base_request)
+    let base_request = input.request_input.request_items.iter().try_fold(
+        base_request,
+        |req_data, item| -> Result<PartialRequestData> {
+            // Failures of file items are told, as their file would be left out
+            let file_item = [
+                parsers_request_items::operators::body_file_value,
+                parsers_request_items::operators::file_value,
+            ]
+            .into_iter()
+            .find_map(|parser| parser(item.as_ref(), &req_data));
+            if let Some(parsed) = file_item {
+                return parsed.map_err(|err| {
+                    anyhow::Error::msg(format!("Invalid request item '{item}': {err}"))
+                });
+            }
+
+            let parsed = [
+                parsers_request_items::operators::query_param_value,
+                parsers_request_items::operators::non_string_body_value,
+                parsers_request_items::operators::body_value,
+                parsers_request_items::operators::header_value,
+            ]
+            .into_iter()
+            .find_map(|parser| parser(item.as_ref(), &req_data));
+
+            match parsed {
+                Some(parsed) => Ok(parsed.unwrap_or(req_data)),
+                // A file alone would be silently left out of the body
+                None if item.starts_with('@') => Err(anyhow::Error::msg(format!(
+                    "Invalid request item '{item}'. To send a file as the body, use '--raw {item}'"
+                ))),
+                None => Ok(req_data),
+            }
+        },
+    )?;
+
+    // Files are only read on submit when the saved request keeps their references
+    if input.request_input.keep_file_references {
+        return Ok(base_request);
+    }
+
+    base_request.with_resolved_body_files()
+}
+
+/// Credentials given in URL are kept apart from it, as the auth of request.
+/// The ones of '--auth' have priority over them
+fn with_auth_of_input(
+    mut request: PartialRequestData,
+    input: &RequestBuildingOptions,
+) -> Result<PartialRequestData> {
+    let url_credentials = match request.url.as_mut() {
+        Some(Url::ValidatedUrl(url)) => url.take_credentials(),
+        _ => None,
+    };
+
+    match input.auth.clone().or(url_credentials) {
+        Some(credentials) => {
+            let auth_type = input.auth_type.unwrap_or_default();
+            Ok(request.with_auth(Auth::init(auth_type, credentials)))
+        }
+        None if input.auth_type.is_some() => Err(anyhow::Error::msg(
+            "'--auth-type' needs credentials, given by '--auth' or in URL",
+        )),
+        None => Ok(request),
+    }
+}
+
+// Saved requests keep working from any directory
+fn absolute_path(path: &str) -> Result<String> {
+    Ok(std::path::absolute(path)?.to_string_lossy().to_string())
 }
 
 mod parsers_request_items {
@@ -79,7 +196,27 @@ mod parsers_request_items {
             let input_key = matcher.name("key")?.as_str();
             let input_value = matcher.name("value")?.as_str();
 
-            let request = base_request.clone();
+            let mut request = base_request.clone();
+
+            // In form and multipart modes, keys are sent as they are written
+            match &mut request.body {
+                Some(BodyPayload::Form(fields)) => {
+                    fields.retain(|(key, _)| key != input_key);
+                    fields.push((input_key.to_string(), input_value.to_string()));
+                    return Some(Ok(request));
+                }
+                Some(BodyPayload::Multipart(fields)) => {
+                    merge_multipart_fields(
+                        fields,
+                        vec![MultipartField::Text {
+                            name: input_key.to_string(),
+                            value: input_value.to_string(),
+                        }],
+                    );
+                    return Some(Ok(request));
+                }
+                _ => {}
+            }
 
             let sub_keys = utils::extract_nested_body_keys(input_key);
             let new_request = utils::build_partial_request_data_with(
@@ -88,8 +225,6 @@ mod parsers_request_items {
                 Value::String(input_value.to_string()),
             );
 
-            println!("{:?}", new_request);
-
             Some(Ok(new_request))
         }
 
@@ -103,7 +238,7 @@ mod parsers_request_items {
             let input_key = matcher.name("key")?.as_str();
             let input_value = matcher.name("value")?.as_str();
 
-            let request = base_request.clone();
+            let mut request = base_request.clone();
 
             let sub_keys = utils::extract_nested_body_keys(input_key);
             let value_to_set = {
@@ -115,12 +250,106 @@ mod parsers_request_items {
                 }
             };
 
+            if let Some(BodyPayload::Multipart(fields)) = &mut request.body {
+                merge_multipart_fields(
+                    fields,
+                    vec![MultipartField::Text {
+                        name: input_key.to_string(),
+                        value: value_to_set.to_string(),
+                    }],
+                );
+                return Some(Ok(request));
+            }
+
             let new_request =
                 utils::build_partial_request_data_with(request, sub_keys, value_to_set);
 
             Some(Ok(new_request))
         }
 
+        pub fn body_file_value(s: &str, base_request: &PartialRequestData) -> Option<ParserResult> {
+            let (matcher, as_json) = regexes::request_items::non_string_body_file_value()
+                .captures(s)
+                .map(|matcher| (matcher, true))
+                .or_else(|| {
+                    regexes::request_items::body_file_value()
+                        .captures(s)
+                        .map(|matcher| (matcher, false))
+                })?;
+
+            let input_key = matcher.name("key")?.as_str();
+            let path = match absolute_path(matcher.name("path")?.as_str()) {
+                Ok(path) => path,
+                Err(err) => return Some(Err(err.into())),
+            };
+
+            // In form and multipart modes, keys are sent as they are written
+            let keys = match base_request.body {
+                Some(BodyPayload::Form(_) | BodyPayload::Multipart(_)) => {
+                    Vec::from([input_key.to_string()])
+                }
+                _ => utils::extract_nested_body_keys(input_key)
+                    .into_iter()
+                    .map(String::from)
+                    .collect(),
+            };
+
+            let mut request = base_request.clone();
+            let mut body_files = request.body_files.take().unwrap_or_default();
+            merge_body_file_references(
+                &mut body_files,
+                vec![BodyFileReference {
+                    keys,
+                    path,
+                    as_json,
+                }],
+            );
+            request.body_files = Some(body_files);
+
+            Some(Ok(request))
+        }
+
+        pub fn file_value(s: &str, base_request: &PartialRequestData) -> Option<ParserResult> {
+            let re = regexes::request_items::file_value();
+            let matcher = re.captures(s)?;
+
+            let name = matcher.name("key")?.as_str().to_string();
+            let path = matcher.name("path")?.as_str();
+            let content_type = matcher
+                .name("content_type")
+                .map(|value| value.as_str().to_string());
+
+            let path = match absolute_path(path) {
+                Ok(path) => path,
+                Err(err) => return Some(Err(err.into())),
+            };
+
+            let mut request = base_request.clone();
+
+            let mut fields = match request.body.take() {
+                Some(BodyPayload::Multipart(fields)) => fields,
+                Some(BodyPayload::Json(Value::Object(map))) => multipart_text_fields(map),
+                Some(BodyPayload::Form(fields)) => fields
+                    .into_iter()
+                    .map(|(name, value)| MultipartField::Text { name, value })
+                    .collect(),
+                _ => Vec::new(),
+            };
+
+            merge_multipart_fields(
+                &mut fields,
+                vec![MultipartField::File {
+                    name,
+                    path,
+                    content_type,
+                }],
+            );
+
+            request.body = Some(BodyPayload::Multipart(fields));
+
+            Some(Ok(request))
+        }
+
         pub fn header_value(s: &str, base_request: &PartialRequestData) -> Option<ParserResult> {
             let re = regexes::request_items::header_value();
             let matcher = re.captures(s)?;
@@ -171,7 +400,7 @@ mod parsers_request_items {
     pub mod utils {
         use super::*;
 
-        pub fn extract_nested_body_keys<'a>(s: &'a str) -> Vec<&'a str> {
+        pub fn extract_nested_body_keys(s: &str) -> Vec<&str> {
             let keys: Option<Vec<&str>> = (|| {
                 let re = regexes::request_items::nested_body_keys();
                 let matcher = re.captures(s)?;
@@ -188,7 +417,7 @@ mod parsers_request_items {
                 Some(
                     Vec::from([root_key])
                         .into_iter()
-                        .chain(sub_keys.into_iter())
+                        .chain(sub_keys)
                         .collect::<Vec<_>>(),
                 )
             })();
@@ -196,7 +425,7 @@ mod parsers_request_items {
             keys.unwrap_or(Vec::from([s]))
         }
 
-        pub fn parse_non_string_value_from_str_input<'a>(input_value: &'a str) -> Option<Value> {
+        pub fn parse_non_string_value_from_str_input(input_value: &str) -> Option<Value> {
             serde_json::from_str::<Value>(input_value)
                 .ok()
                 .and_then(|v| match v {
@@ -268,7 +497,7 @@ pub mod tests_parsers_request_items {
 
         for case in cases {
             let base_request = PartialRequestData::default();
-            let output = operators::non_string_body_value(case, &base_request); 
+            let output = operators::non_string_body_value(case, &base_request);
             assert!(matches!(output, Some(Err(_))));
         }
     }
@@ -15,6 +15,12 @@ pub trait CliWriterRepository: Send {
 
     fn print_lines<T: Display>(&mut self, lines: impl IntoIterator<Item = T>);
 
+    /// Writes the content exactly as given, like binary response bodies
+    fn print_bytes(&mut self, bytes: &[u8]);
+
+    /// If output goes to a terminal, instead of a pipe or file
+    fn is_terminal(&self) -> bool;
+
     fn print_animation_single_line<T: Display, Sprites: IntoIterator<Item = T> + Sized + Clone>(
         &mut self,
         sprites: Sprites,
@@ -34,14 +40,21 @@ pub trait CliWriterRepository: Send {
 
 pub struct CrosstermCliWriter {
     pub stdout: Box<dyn Write + Send>,
+    pub is_terminal: bool,
 }
 
 impl CrosstermCliWriter {
     pub fn from<W: Write + Send + 'static>(writer: W) -> Self {
         Self {
             stdout: Box::new(writer),
+            is_terminal: false,
         }
     }
+
+    pub fn with_is_terminal(mut self, value: bool) -> Self {
+        self.is_terminal = value;
+        self
+    }
 }
 
 impl CliWriterRepository for CrosstermCliWriter {
@@ -61,6 +74,15 @@ impl CliWriterRepository for CrosstermCliWriter {
         self.stdout.flush().unwrap();
     }
 
+    fn print_bytes(&mut self, bytes: &[u8]) {
+        self.stdout.write_all(bytes).unwrap();
+        self.stdout.flush().unwrap();
+    }
+
+    fn is_terminal(&self) -> bool {
+        self.is_terminal
+    }
+
     fn print_lines_styled<'a, StyledValues>(
         &mut self,
         lines: impl IntoIterator<Item = StyledValues>,
@@ -76,6 +76,33 @@ fn should_merge_nested_body_values_with_saved() {
     );
 }
 
+#[test]
+fn should_merge_form_values_with_saved() {
+    let input = format!(
+        "treq POST {}/post --form user=John job=dev --save-as login-form",
+        host()
+    );
+    let mut cmd = run_cmd(&input);
+    cmd.assert().success();
+    cmd.assert().stdout(
+        predicate::str::contains(r#""form""#).and(predicate::str::contains(
+            "application/x-www-form-urlencoded",
+        )),
+    );
+
+    // Saved request keeps sending a form, without the flag
+    let input = "treq run login-form user=Jane";
+    let mut cmd = run_cmd(&input);
+    cmd.assert().success();
+    cmd.assert().stdout(
+        predicate::str::contains("Jane")
+            .and(predicate::str::contains("dev"))
+            .and(predicate::str::contains(
+                "application/x-www-form-urlencoded",
+            )),
+    );
+}
+
 #[test]
 fn should_assert_list_saved_requests() {
     let requests_to_save = ["simple-get", "some-put", "a-great-post"];
@@ -353,5 +380,8 @@ fn run_cmd(input: &str) -> Command {
         cmd.arg(arg);
     }
 
+    // Never wait for a body from the stdin of test runner
+    cmd.write_stdin("");
+
     cmd
 }
@@ -1,10 +1,15 @@
+use treq::app::backend::Backend;
+use treq::app::services::request::entities::cookies::CookieJar;
 use treq::app::services::request::entities::methods::METHODS;
 use treq::app::services::request::entities::partial_entities::PartialRequestData;
 use treq::app::services::request::entities::requests::{BodyPayload, RequestData};
 use treq::app::services::request::entities::url::Url;
+use treq::app::services::web_client::entities::{FailedAttempt, Response, ResponseStage, Timings};
+use treq::utils::errors::RequestCancelled;
 use treq::view::commands::{self, ViewCommand};
+use treq::view::input::cli_input::{SessionOptions, ViewOptions};
 
-use crate::mocks::repositories::{create_mock_back_end, CliWriterUseLess};
+use crate::mocks::repositories::{create_mock_back_end, CliWriterRecorder, CliWriterUseLess};
 
 #[tokio::test]
 async fn should_submit_a_basic_request() -> anyhow::Result<()> {
@@ -17,6 +22,7 @@ async fn should_submit_a_basic_request() -> anyhow::Result<()> {
 
     let executor: Box<dyn ViewCommand> = BasicRequestExecutor {
         request: request_to_do.clone(),
+        view_options: ViewOptions::default(),
         writer_metadata: CliWriterUseLess,
         writer_response: CliWriterUseLess,
         writer_stderr: CliWriterUseLess,
@@ -46,6 +52,7 @@ async fn should_submit_a_request_after_saved() -> anyhow::Result<()> {
         method: Some(METHODS::POST),
         body: Some(BodyPayload::Json(serde_json::json!({ "Hello": "World" }))),
         headers: None,
+        ..Default::default()
     };
 
     // Merge of first and input of second request
@@ -60,6 +67,7 @@ async fn should_submit_a_request_after_saved() -> anyhow::Result<()> {
 
     let basic_request_executor: Box<dyn ViewCommand> = BasicRequestExecutor {
         request: first_request_to_do.clone(),
+        view_options: ViewOptions::default(),
         writer_metadata: CliWriterUseLess,
         writer_response: CliWriterUseLess,
         writer_stderr: CliWriterUseLess,
@@ -87,6 +95,7 @@ async fn should_submit_a_request_after_saved() -> anyhow::Result<()> {
     let submit_save_request_executor: Box<dyn ViewCommand> = SubmitSavedRequestExecutor {
         request_name: "some_request".into(),
         input_request_data: PartialRequestData::default(),
+        view_options: ViewOptions::default(),
         writer_metadata: CliWriterUseLess,
         writer_response: CliWriterUseLess,
         writer_stderr: CliWriterUseLess,
@@ -97,3 +106,402 @@ async fn should_submit_a_request_after_saved() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+fn binary_response() -> Response {
+    let body_bytes = vec![0x89, b'P', b'N', b'G', 0x00, 0xff];
+    Response {
+        status: 200,
+        headers: vec![("content-type".into(), "image/png".into())],
+        body_bytes,
+        stage: ResponseStage::Finished,
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn should_not_print_binary_response_to_terminal() -> anyhow::Result<()> {
+    use commands::submit_request::BasicRequestExecutor;
+
+    let request_to_do = RequestData::default().with_url("https://example.com/image.png");
+    let writer_response = CliWriterRecorder::terminal();
+    let writer_stderr = CliWriterRecorder::default();
+
+    let executor: Box<dyn ViewCommand> = BasicRequestExecutor {
+        request: request_to_do.clone(),
+        view_options: ViewOptions::default(),
+        writer_metadata: CliWriterUseLess,
+        writer_response: writer_response.clone(),
+        writer_stderr: writer_stderr.clone(),
+    }
+    .into();
+
+    let mut backend = create_mock_back_end()
+        .with_expected_requests([request_to_do])
+        .with_response(binary_response());
+    executor.execute(&mut backend).await?;
+
+    assert!(writer_response.output().is_empty());
+    let summary = writer_stderr.output_text();
+    assert!(summary.contains("6 bytes"), "{summary}");
+    assert!(summary.contains("image/png"), "{summary}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn should_write_binary_response_as_is_when_piped() -> anyhow::Result<()> {
+    use commands::submit_request::BasicRequestExecutor;
+
+    let request_to_do = RequestData::default().with_url("https://example.com/image.png");
+    let writer_response = CliWriterRecorder::default();
+
+    let executor: Box<dyn ViewCommand> = BasicRequestExecutor {
+        request: request_to_do.clone(),
+        view_options: ViewOptions::default(),
+        writer_metadata: CliWriterUseLess,
+        writer_response: writer_response.clone(),
+        writer_stderr: CliWriterUseLess,
+    }
+    .into();
+
+    let mut backend = create_mock_back_end()
+        .with_expected_requests([request_to_do])
+        .with_response(binary_response());
+    executor.execute(&mut backend).await?;
+
+    assert_eq!(binary_response().body_bytes, writer_response.output());
+    Ok(())
+}
+
+#[tokio::test]
+async fn should_keep_cookies_and_headers_in_session() -> anyhow::Result<()> {
+    use commands::submit_request::BasicRequestExecutor;
+
+    let mut cookies = CookieJar::default();
+    cookies.store_from_headers(
+        &"https://example.com/login".parse()?,
+        ["session=abc; Path=/"],
+    );
+    let login_request = RequestData::default()
+        .with_url("https://example.com/login")
+        .with_headers([("Authorization".into(), "Bearer abc".into())]);
+    let me_request = RequestData::default().with_url("https://example.com/me");
+
+    let session_options = |read_only| ViewOptions {
+        session: Some(SessionOptions {
+            name: "app".into(),
+            read_only,
+        }),
+        ..Default::default()
+    };
+
+    let mut backend = create_mock_back_end()
+        .with_expected_requests([
+            login_request.clone().with_cookies(CookieJar::default()),
+            me_request
+                .clone()
+                .with_headers([("Authorization".into(), "Bearer abc".into())])
+                .with_cookies(cookies.clone()),
+        ])
+        .with_response(Response {
+            status: 200,
+            cookies: Some(cookies.clone()),
+            ..Default::default()
+        });
+
+    for (request, read_only) in [(login_request, false), (me_request, true)] {
+        let executor: Box<dyn ViewCommand> = BasicRequestExecutor {
+            request,
+            view_options: session_options(read_only),
+            writer_metadata: CliWriterUseLess,
+            writer_response: CliWriterUseLess,
+            writer_stderr: CliWriterUseLess,
+        }
+        .into();
+        executor.execute(&mut backend).await?;
+    }
+
+    let session = backend.get_session("app".into()).await?;
+    assert_eq!(cookies, session.cookies);
+    assert_eq!(
+        Some(&"Bearer abc".to_string()),
+        session
+            .headers
+            .get("example.com")
+            .and_then(|headers| headers.get("Authorization"))
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn should_print_http_version_in_status() -> anyhow::Result<()> {
+    use commands::submit_request::BasicRequestExecutor;
+
+    let request_to_do = RequestData::default().with_url("https://example.com");
+    let writer_metadata = CliWriterRecorder::default();
+
+    let executor: Box<dyn ViewCommand> = BasicRequestExecutor {
+        request: request_to_do.clone(),
+        view_options: ViewOptions::default(),
+        writer_metadata: writer_metadata.clone(),
+        writer_response: CliWriterUseLess,
+        writer_stderr: CliWriterUseLess,
+    }
+    .into();
+
+    let mut backend = create_mock_back_end()
+        .with_expected_requests([request_to_do])
+        .with_response(Response {
+            status: 200,
+            version: Some("HTTP/2.0".into()),
+            stage: ResponseStage::Finished,
+            ..Default::default()
+        });
+    executor.execute(&mut backend).await?;
+
+    let metadata = writer_metadata.output_text();
+    assert!(metadata.contains("STATUS: HTTP/2.0 200 (OK)"), "{metadata}");
+    Ok(())
+}
+
+#[tokio::test]
+async fn should_print_timings_under_status() -> anyhow::Result<()> {
+    use commands::submit_request::BasicRequestExecutor;
+
+    let request_to_do = RequestData::default().with_url("https://example.com");
+    let writer_metadata = CliWriterRecorder::default();
+
+    let executor: Box<dyn ViewCommand> = BasicRequestExecutor {
+        request: request_to_do.clone(),
+        view_options: ViewOptions {
+            print_timings: true,
+            ..Default::default()
+        },
+        writer_metadata: writer_metadata.clone(),
+        writer_response: CliWriterUseLess,
+        writer_stderr: CliWriterUseLess,
+    }
+    .into();
+
+    let mut backend = create_mock_back_end()
+        .with_expected_requests([request_to_do])
+        .with_response(Response {
+            status: 200,
+            timings: Timings {
+                dns_ms: Some(4),
+                connect_ms: Some(2),
+                tls_ms: Some(9),
+                ttfb_ms: 120,
+                download_ms: 35,
+            },
+            stage: ResponseStage::Finished,
+            ..Default::default()
+        });
+    executor.execute(&mut backend).await?;
+
+    let metadata = writer_metadata.output_text();
+    assert!(
+        metadata
+            .contains("TIMINGS: DNS 4 MS | CONNECT 2 MS | TLS 9 MS | TTFB 120 MS | DOWNLOAD 35 MS"),
+        "{metadata}"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn should_print_timings_as_json_with_body_only() -> anyhow::Result<()> {
+    use commands::submit_request::BasicRequestExecutor;
+
+    let request_to_do = RequestData::default().with_url("https://example.com");
+    let writer_stderr = CliWriterRecorder::default();
+
+    let executor: Box<dyn ViewCommand> = BasicRequestExecutor {
+        request: request_to_do.clone(),
+        view_options: ViewOptions {
+            print_timings: true,
+            print_body_only: true,
+            ..Default::default()
+        },
+        writer_metadata: CliWriterUseLess,
+        writer_response: CliWriterUseLess,
+        writer_stderr: writer_stderr.clone(),
+    }
+    .into();
+
+    let mut backend = create_mock_back_end()
+        .with_expected_requests([request_to_do])
+        .with_response(Response {
+            status: 200,
+            timings: Timings {
+                dns_ms: None,
+                connect_ms: Some(2),
+                tls_ms: None,
+                ttfb_ms: 120,
+                download_ms: 35,
+            },
+            stage: ResponseStage::Finished,
+            ..Default::default()
+        });
+    executor.execute(&mut backend).await?;
+
+    let stderr = writer_stderr.output_text();
+    assert!(
+        stderr.contains(r#"{"connect_ms":2,"ttfb_ms":120,"download_ms":35}"#),
+        "{stderr}"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn should_print_each_failed_attempt() -> anyhow::Result<()> {
+    use commands::submit_request::BasicRequestExecutor;
+
+    let request_to_do = RequestData::default().with_url("https://example.com");
+    let writer_metadata = CliWriterRecorder::default();
+
+    let executor: Box<dyn ViewCommand> = BasicRequestExecutor {
+        request: request_to_do.clone(),
+        view_options: ViewOptions::default(),
+        writer_metadata: writer_metadata.clone(),
+        writer_response: CliWriterUseLess,
+        writer_stderr: CliWriterUseLess,
+    }
+    .into();
+
+    let mut backend = create_mock_back_end()
+        .with_expected_requests([request_to_do])
+        .with_response(Response {
+            status: 200,
+            attempts: 3,
+            failed_attempts: vec![
+                FailedAttempt {
+                    status: Some(503),
+                    error: None,
+                    wait_ms: 500,
+                },
+                FailedAttempt {
+                    status: None,
+                    error: Some("connection reset".into()),
+                    wait_ms: 1000,
+                },
+            ],
+            stage: ResponseStage::Finished,
+            ..Default::default()
+        });
+    executor.execute(&mut backend).await?;
+
+    let metadata = writer_metadata.output_text();
+    assert!(
+        metadata.contains("ATTEMPT 1: 503 (Service Unavailable)    retrying in 500 MS"),
+        "{metadata}"
+    );
+    assert!(
+        metadata.contains("ATTEMPT 2: connection reset    retrying in 1000 MS"),
+        "{metadata}"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn should_print_cancelled_request() -> anyhow::Result<()> {
+    use commands::submit_request::BasicRequestExecutor;
+
+    let request_to_do = RequestData::default().with_url("https://example.com");
+    let writer_stderr = CliWriterRecorder::default();
+
+    let executor: Box<dyn ViewCommand> = BasicRequestExecutor {
+        request: request_to_do.clone(),
+        view_options: ViewOptions::default(),
+        writer_metadata: CliWriterUseLess,
+        writer_response: CliWriterUseLess,
+        writer_stderr: writer_stderr.clone(),
+    }
+    .into();
+
+    let mut backend = create_mock_back_end()
+        .with_expected_requests([request_to_do])
+        .with_response(Response {
+            response_time_ms: 250,
+            stage: ResponseStage::Cancelled,
+            ..Default::default()
+        });
+    let err = executor.execute(&mut backend).await.unwrap_err();
+
+    assert!(err.is::<RequestCancelled>());
+    let stderr = writer_stderr.output_text();
+    assert!(
+        stderr.contains("Request cancelled after 250 MS"),
+        "{stderr}"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn should_print_address_connected_to() -> anyhow::Result<()> {
+    use commands::submit_request::BasicRequestExecutor;
+
+    let request_to_do = RequestData::default().with_url("https://example.com");
+    let writer_metadata = CliWriterRecorder::default();
+
+    let executor: Box<dyn ViewCommand> = BasicRequestExecutor {
+        request: request_to_do.clone(),
+        view_options: ViewOptions::default(),
+        writer_metadata: writer_metadata.clone(),
+        writer_response: CliWriterUseLess,
+        writer_stderr: CliWriterUseLess,
+    }
+    .into();
+
+    let mut backend = create_mock_back_end()
+        .with_expected_requests([request_to_do])
+        .with_response(Response {
+            status: 200,
+            remote_addr: Some("10.0.0.5:443".into()),
+            stage: ResponseStage::Finished,
+            ..Default::default()
+        });
+    executor.execute(&mut backend).await?;
+
+    let metadata = writer_metadata.output_text();
+    assert!(
+        metadata.contains("CONNECTED TO: 10.0.0.5:443"),
+        "{metadata}"
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn should_print_wire_and_decoded_size_of_body() -> anyhow::Result<()> {
+    use commands::submit_request::BasicRequestExecutor;
+
+    let request_to_do = RequestData::default().with_url("https://example.com");
+    let writer_metadata = CliWriterRecorder::default();
+
+    let executor: Box<dyn ViewCommand> = BasicRequestExecutor {
+        request: request_to_do.clone(),
+        view_options: ViewOptions::default(),
+        writer_metadata: writer_metadata.clone(),
+        writer_response: CliWriterUseLess,
+        writer_stderr: CliWriterUseLess,
+    }
+    .into();
+
+    let mut backend = create_mock_back_end()
+        .with_expected_requests([request_to_do])
+        .with_response(Response {
+            status: 200,
+            headers: vec![("content-encoding".into(), "br".into())],
+            body: "Hello World".into(),
+            body_bytes: "Hello World".into(),
+            wire_size: Some(15),
+            stage: ResponseStage::Finished,
+            ..Default::default()
+        });
+    executor.execute(&mut backend).await?;
+
+    let metadata = writer_metadata.output_text();
+    assert!(
+        metadata.contains("SIZE: 11 bytes decoded, 15 bytes on wire (br)"),
+        "{metadata}"
+    );
+    Ok(())
+}
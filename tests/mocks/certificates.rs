@@ -0,0 +1,103 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use rcgen::{BasicConstraints, Certificate, CertificateParams, ExtendedKeyUsagePurpose, IsCa};
+use tokio_rustls::rustls;
+use tokio_rustls::rustls::server::AllowAnyAuthenticatedClient;
+use tokio_rustls::rustls::{RootCertStore, ServerConfig};
+
+/// A private CA, with a server certificate for 'localhost' and a client certificate
+/// signed by it. PEM files of client side are written to a temp dir.
+pub struct TestCertificates {
+    ca: Certificate,
+    server: Certificate,
+    client: Certificate,
+    dir: tempfile::TempDir,
+}
+
+impl TestCertificates {
+    pub fn generate() -> Self {
+        let ca = {
+            let mut params = CertificateParams::new(vec![]);
+            params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+            Certificate::from_params(params).unwrap()
+        };
+        let server =
+            Certificate::from_params(CertificateParams::new(vec!["localhost".into()])).unwrap();
+        let client = {
+            let mut params = CertificateParams::new(vec!["treq-client".into()]);
+            params.extended_key_usages = vec![ExtendedKeyUsagePurpose::ClientAuth];
+            Certificate::from_params(params).unwrap()
+        };
+
+        let certificates = Self {
+            ca,
+            server,
+            client,
+            dir: tempfile::tempdir().unwrap(),
+        };
+
+        std::fs::write(
+            certificates.ca_path(),
+            certificates.ca.serialize_pem().unwrap(),
+        )
+        .unwrap();
+        std::fs::write(
+            certificates.client_cert_path(),
+            certificates
+                .client
+                .serialize_pem_with_signer(&certificates.ca)
+                .unwrap(),
+        )
+        .unwrap();
+        std::fs::write(
+            certificates.client_key_path(),
+            certificates.client.serialize_private_key_pem(),
+        )
+        .unwrap();
+
+        certificates
+    }
+
+    pub fn ca_path(&self) -> PathBuf {
+        self.path("ca.pem")
+    }
+
+    pub fn client_cert_path(&self) -> PathBuf {
+        self.path("client.pem")
+    }
+
+    pub fn client_key_path(&self) -> PathBuf {
+        self.path("client-key.pem")
+    }
+
+    fn path(&self, file: impl AsRef<Path>) -> PathBuf {
+        self.dir.path().join(file)
+    }
+
+    /// Config of a server using the 'localhost' certificate, asking for a client
+    /// certificate of same CA when `require_client_cert`
+    pub fn server_config(&self, require_client_cert: bool) -> Arc<ServerConfig> {
+        let cert_chain = vec![rustls::Certificate(
+            self.server.serialize_der_with_signer(&self.ca).unwrap(),
+        )];
+        let key = rustls::PrivateKey(self.server.serialize_private_key_der());
+
+        let builder = ServerConfig::builder().with_safe_defaults();
+        let config = if require_client_cert {
+            let mut roots = RootCertStore::empty();
+            roots
+                .add(&rustls::Certificate(self.ca.serialize_der().unwrap()))
+                .unwrap();
+            builder
+                .with_client_cert_verifier(AllowAnyAuthenticatedClient::new(roots).boxed())
+                .with_single_cert(cert_chain, key)
+        } else {
+            builder
+                .with_no_client_auth()
+                .with_single_cert(cert_chain, key)
+        };
+
+        Arc::new(config.unwrap())
+    }
+}
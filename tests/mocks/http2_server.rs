@@ -0,0 +1,73 @@
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use hyper::server::conn::Http;
+use hyper::service::service_fn;
+use hyper::{Body, Request, Response};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::TlsAcceptor;
+
+/// Server which only speaks HTTP/2, answering every request with the HTTP version
+/// it was received with. Plaintext (h2c) unless a TLS config is given, which is
+/// made to offer only 'h2' by ALPN
+pub struct MockHttp2Server {
+    pub addr: SocketAddr,
+    scheme: &'static str,
+    task: JoinHandle<()>,
+}
+
+impl MockHttp2Server {
+    pub async fn start(tls_config: Option<Arc<ServerConfig>>) -> Self {
+        let scheme = if tls_config.is_some() {
+            "https"
+        } else {
+            "http"
+        };
+        let tls_acceptor = tls_config.map(|config| {
+            let mut config = (*config).clone();
+            config.alpn_protocols = vec![b"h2".to_vec()];
+            TlsAcceptor::from(Arc::new(config))
+        });
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let task = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let tls_acceptor = tls_acceptor.clone();
+                tokio::spawn(async move {
+                    let service = service_fn(|req: Request<Body>| async move {
+                        let version = format!("{:?}", req.version());
+                        Ok::<_, Infallible>(Response::new(Body::from(version)))
+                    });
+                    let http = Http::new().http2_only(true).clone();
+
+                    match tls_acceptor {
+                        Some(acceptor) => {
+                            if let Ok(stream) = acceptor.accept(stream).await {
+                                http.serve_connection(stream, service).await.ok();
+                            }
+                        }
+                        None => {
+                            http.serve_connection(stream, service).await.ok();
+                        }
+                    }
+                });
+            }
+        });
+
+        Self { addr, scheme, task }
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("{}://localhost:{}{path}", self.scheme, self.addr.port())
+    }
+}
+
+impl Drop for MockHttp2Server {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
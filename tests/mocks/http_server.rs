@@ -0,0 +1,270 @@
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::TlsAcceptor;
+
+type Handler = dyn Fn(&ReceivedRequest) -> MockResponse + Send + Sync;
+
+/// Tiny HTTP/1.1 server bound to a random local port, answering every request with
+/// the response built by the given handler. Connections are kept alive.
+pub struct MockHttpServer {
+    pub addr: SocketAddr,
+    scheme: &'static str,
+    accepted_connections: Arc<AtomicUsize>,
+    received_requests: Arc<Mutex<Vec<ReceivedRequest>>>,
+    task: JoinHandle<()>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReceivedRequest {
+    pub method: String,
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl ReceivedRequest {
+    pub fn header(&self, key: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    pub delay: Option<Duration>,
+}
+
+impl MockResponse {
+    pub fn new(status: u16) -> Self {
+        Self {
+            status,
+            ..Default::default()
+        }
+    }
+
+    pub fn with_header(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((key.into(), value.into()));
+        self
+    }
+
+    pub fn with_body(mut self, body: impl Into<Vec<u8>>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut head = format!("HTTP/1.1 {} Mock\r\n", self.status);
+        for (key, value) in &self.headers {
+            head.push_str(&format!("{key}: {value}\r\n"));
+        }
+        let has_content_length = self
+            .headers
+            .iter()
+            .any(|(k, _)| k.eq_ignore_ascii_case("content-length"));
+        if !has_content_length {
+            head.push_str(&format!("Content-Length: {}\r\n", self.body.len()));
+        }
+        head.push_str("\r\n");
+
+        let mut bytes = head.into_bytes();
+        bytes.extend_from_slice(&self.body);
+        bytes
+    }
+}
+
+impl MockHttpServer {
+    pub async fn start(
+        handler: impl Fn(&ReceivedRequest) -> MockResponse + Send + Sync + 'static,
+    ) -> Self {
+        Self::start_with_tls(None, handler).await
+    }
+
+    /// Same as `start`, but serving HTTPS when a TLS config is given
+    pub async fn start_with_tls(
+        tls_config: Option<Arc<ServerConfig>>,
+        handler: impl Fn(&ReceivedRequest) -> MockResponse + Send + Sync + 'static,
+    ) -> Self {
+        let scheme = if tls_config.is_some() {
+            "https"
+        } else {
+            "http"
+        };
+        let tls_acceptor = tls_config.map(TlsAcceptor::from);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let accepted_connections = Arc::new(AtomicUsize::new(0));
+        let received_requests = Arc::new(Mutex::new(Vec::new()));
+        let handler: Arc<Handler> = Arc::new(handler);
+
+        let task = {
+            let accepted_connections = accepted_connections.clone();
+            let received_requests = received_requests.clone();
+            tokio::spawn(async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    accepted_connections.fetch_add(1, Ordering::SeqCst);
+                    let received_requests = received_requests.clone();
+                    let handler = handler.clone();
+                    match tls_acceptor.clone() {
+                        Some(tls_acceptor) => {
+                            tokio::spawn(async move {
+                                // Handshakes refused by client or server just close the connection
+                                if let Ok(stream) = tls_acceptor.accept(stream).await {
+                                    serve_connection(stream, handler, received_requests).await;
+                                }
+                            });
+                        }
+                        None => {
+                            tokio::spawn(serve_connection(stream, handler, received_requests));
+                        }
+                    }
+                }
+            })
+        };
+
+        Self {
+            addr,
+            scheme,
+            accepted_connections,
+            received_requests,
+            task,
+        }
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("{}://localhost:{}{}", self.scheme, self.addr.port(), path)
+    }
+
+    pub fn accepted_connections(&self) -> usize {
+        self.accepted_connections.load(Ordering::SeqCst)
+    }
+
+    pub fn received_requests(&self) -> Vec<ReceivedRequest> {
+        self.received_requests.lock().unwrap().clone()
+    }
+}
+
+impl Drop for MockHttpServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn serve_connection(
+    mut stream: impl AsyncRead + AsyncWrite + Unpin,
+    handler: Arc<Handler>,
+    received_requests: Arc<Mutex<Vec<ReceivedRequest>>>,
+) {
+    let mut buffer: Vec<u8> = Vec::new();
+
+    while let Some(request) = read_request(&mut stream, &mut buffer).await {
+        let response = handler(&request);
+        received_requests.lock().unwrap().push(request);
+
+        if let Some(delay) = response.delay {
+            tokio::time::sleep(delay).await;
+        }
+
+        if stream.write_all(&response.to_bytes()).await.is_err() {
+            return;
+        }
+    }
+}
+
+async fn read_request(
+    stream: &mut (impl AsyncRead + Unpin),
+    buffer: &mut Vec<u8>,
+) -> Option<ReceivedRequest> {
+    let head_end = loop {
+        if let Some(pos) = find(buffer, b"\r\n\r\n") {
+            break pos;
+        }
+        read_more(stream, buffer).await?;
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..head_end]).to_string();
+    buffer.drain(..head_end + 4);
+
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next()?.split(' ');
+    let method = request_line.next()?.to_string();
+    let path = request_line.next()?.to_string();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .collect();
+
+    let mut request = ReceivedRequest {
+        method,
+        path,
+        headers,
+        body: Vec::new(),
+    };
+
+    if let Some(length) = request.header("content-length") {
+        let length: usize = length.parse().ok()?;
+        while buffer.len() < length {
+            read_more(stream, buffer).await?;
+        }
+        request.body = buffer.drain(..length).collect();
+    } else if request
+        .header("transfer-encoding")
+        .is_some_and(|v| v.eq_ignore_ascii_case("chunked"))
+    {
+        loop {
+            let line_end = loop {
+                if let Some(pos) = find(buffer, b"\r\n") {
+                    break pos;
+                }
+                read_more(stream, buffer).await?;
+            };
+            let size_line = String::from_utf8_lossy(&buffer[..line_end]).to_string();
+            let size = usize::from_str_radix(size_line.split(';').next()?.trim(), 16).ok()?;
+            buffer.drain(..line_end + 2);
+
+            while buffer.len() < size + 2 {
+                read_more(stream, buffer).await?;
+            }
+            request.body.extend(buffer.drain(..size));
+            buffer.drain(..2);
+
+            if size == 0 {
+                break;
+            }
+        }
+    }
+
+    Some(request)
+}
+
+async fn read_more(stream: &mut (impl AsyncRead + Unpin), buffer: &mut Vec<u8>) -> Option<()> {
+    let mut chunk = [0u8; 4096];
+    let read = stream.read(&mut chunk).await.ok()?;
+    if read == 0 {
+        return None;
+    }
+    buffer.extend_from_slice(&chunk[..read]);
+    Some(())
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
@@ -1 +1,6 @@
+pub mod certificates;
+pub mod http2_server;
+pub mod http_server;
 pub mod repositories;
+pub mod tunnel_proxy;
+pub mod unix_socket_server;
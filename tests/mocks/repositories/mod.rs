@@ -1,17 +1,22 @@
+use std::collections::HashMap;
 use std::fmt::Display;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use anyhow::Result;
 use async_trait::async_trait;
 use tempfile::{tempdir, TempDir};
+use tokio::sync::mpsc::UnboundedSender;
 use tokio::sync::oneshot;
 use treq::app::backend::{AppBackend, Backend};
+use treq::app::services::auth::service::AuthService;
 use treq::app::services::files::service::FileService;
 use treq::app::services::request::entities::requests::RequestData;
+use treq::app::services::request::entities::sessions::Session;
 use treq::app::services::request::service::RequestService;
-use treq::app::services::web_client::entities::Response;
-use treq::app::services::web_client::repository_client::reqwest::ReqwestClientRepository;
+use treq::app::services::web_client::downloads::DownloadTarget;
+use treq::app::services::web_client::entities::{FailedAttempt, Response};
+use treq::app::services::web_client::repository_client::hyper_client::HyperClientRepository;
 use treq::app::services::web_client::service::WebClient;
 use treq::utils::uuid::UUID;
 use treq::view::output::writer::CliWriterRepository;
@@ -31,15 +36,17 @@ pub fn create_mock_back_end() -> MockAppBackend {
         .unwrap();
 
     let req = RequestService::init();
-    let web = WebClient::init(ReqwestClientRepository);
+    let web = WebClient::init(HyperClientRepository::default());
     let files = FileService::init(config_dir, data_dir, tempfiles_dir);
-    let backend = AppBackend::init(req, web, files);
+    let auth = AuthService::init(HashMap::new(), HyperClientRepository::default());
+    let backend = AppBackend::init(req, web, files, auth);
     MockAppBackend::new(backend, temp_root)
 }
 
 pub struct MockAppBackend {
     app_backend: AppBackend,
     expected_requests: Vec<RequestData>,
+    response: Response,
     _temp_dir: TempDir,
 }
 
@@ -48,6 +55,7 @@ impl MockAppBackend {
         Self {
             app_backend,
             expected_requests: vec![],
+            response: Response::default(),
             _temp_dir: temp_dir,
         }
     }
@@ -59,6 +67,12 @@ impl MockAppBackend {
         self.expected_requests = expected_requests.into_iter().collect();
         self
     }
+
+    /// Response returned to every submitted request
+    pub fn with_response(mut self, response: Response) -> Self {
+        self.response = response;
+        self
+    }
 }
 
 #[async_trait]
@@ -70,16 +84,31 @@ impl Backend for MockAppBackend {
     async fn submit_request_async(
         &mut self,
         id: UUID,
+        attempts: Option<UnboundedSender<FailedAttempt>>,
     ) -> Result<oneshot::Receiver<Result<Response>>> {
         let request = self.app_backend.get_request(id).await?.unwrap();
         let expected_request = self.expected_requests.remove(0);
         assert_eq!(Arc::new(expected_request), request);
 
+        if let Some(attempts) = attempts {
+            for attempt in &self.response.failed_attempts {
+                attempts.send(attempt.clone()).unwrap();
+            }
+        }
         let (tx, rx) = oneshot::channel();
-        tx.send(Ok(Response::default())).unwrap();
+        tx.send(Ok(self.response.clone())).unwrap();
         Ok(rx)
     }
 
+    async fn download_request_async(
+        &mut self,
+        id: UUID,
+        _target: DownloadTarget,
+        attempts: Option<UnboundedSender<FailedAttempt>>,
+    ) -> Result<oneshot::Receiver<Result<Response>>> {
+        self.submit_request_async(id, attempts).await
+    }
+
     async fn add_request(&mut self, request: RequestData) -> Result<UUID> {
         self.app_backend.add_request(request).await
     }
@@ -131,6 +160,14 @@ impl Backend for MockAppBackend {
             .rename_request_saved(request_name, new_name)
             .await
     }
+
+    async fn get_session(&mut self, name: String) -> Result<Session> {
+        self.app_backend.get_session(name).await
+    }
+
+    async fn save_session(&mut self, name: String, session: Session) -> Result<()> {
+        self.app_backend.save_session(name, session).await
+    }
 }
 
 pub struct CliWriterUseLess;
@@ -140,6 +177,12 @@ impl CliWriterRepository for CliWriterUseLess {
 
     fn print_lines<T: Display>(&mut self, _lines: impl IntoIterator<Item = T>) {}
 
+    fn print_bytes(&mut self, _bytes: &[u8]) {}
+
+    fn is_terminal(&self) -> bool {
+        false
+    }
+
     fn print_animation_single_line<T: Display, Sprites: IntoIterator<Item = T> + Sized + Clone>(
         &mut self,
         _sprites: Sprites,
@@ -160,3 +203,69 @@ impl CliWriterRepository for CliWriterUseLess {
     {
     }
 }
+
+/// Keeps everything written, as plain text, to be checked after the command runs
+#[derive(Clone, Default)]
+pub struct CliWriterRecorder {
+    pub output: Arc<Mutex<Vec<u8>>>,
+    pub is_terminal: bool,
+}
+
+impl CliWriterRecorder {
+    pub fn terminal() -> Self {
+        Self {
+            is_terminal: true,
+            ..Default::default()
+        }
+    }
+
+    pub fn output(&self) -> Vec<u8> {
+        self.output.lock().unwrap().clone()
+    }
+
+    pub fn output_text(&self) -> String {
+        String::from_utf8_lossy(&self.output()).to_string()
+    }
+}
+
+impl CliWriterRepository for CliWriterRecorder {
+    fn clear_current_line(&mut self) {}
+
+    fn print_lines<T: Display>(&mut self, lines: impl IntoIterator<Item = T>) {
+        for line in lines {
+            self.print_bytes(format!("{line}\n").as_bytes());
+        }
+    }
+
+    fn print_bytes(&mut self, bytes: &[u8]) {
+        self.output.lock().unwrap().extend_from_slice(bytes);
+    }
+
+    fn is_terminal(&self) -> bool {
+        self.is_terminal
+    }
+
+    fn print_animation_single_line<T: Display, Sprites: IntoIterator<Item = T> + Sized + Clone>(
+        &mut self,
+        _sprites: Sprites,
+        _interval: Duration,
+        _finisher: oneshot::Receiver<()>,
+    ) where
+        <Sprites as IntoIterator>::IntoIter: Clone,
+    {
+    }
+
+    fn print_centered_text_with_border(&mut self, _text: &str, _border_char: char) {}
+
+    fn print_lines_styled<'a, StyledValues>(
+        &mut self,
+        lines: impl IntoIterator<Item = StyledValues>,
+    ) where
+        StyledValues: IntoIterator<Item = StyledStr<'a>>,
+    {
+        for line in lines {
+            let line: String = line.into_iter().map(|word| word.value).collect();
+            self.print_lines([line]);
+        }
+    }
+}
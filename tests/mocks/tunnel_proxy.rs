@@ -0,0 +1,84 @@
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+/// Proxy answering CONNECT requests by tunneling them to the host asked, which keeps
+/// the head of each CONNECT request received
+pub struct MockTunnelProxy {
+    pub addr: std::net::SocketAddr,
+    received_heads: Arc<Mutex<Vec<String>>>,
+    task: JoinHandle<()>,
+}
+
+impl MockTunnelProxy {
+    pub async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let received_heads = Arc::new(Mutex::new(Vec::new()));
+
+        let task = {
+            let received_heads = received_heads.clone();
+            tokio::spawn(async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    let received_heads = received_heads.clone();
+                    tokio::spawn(async move {
+                        tunnel(stream, received_heads).await;
+                    });
+                }
+            })
+        };
+
+        Self {
+            addr,
+            received_heads,
+            task,
+        }
+    }
+
+    pub fn received_heads(&self) -> Vec<String> {
+        self.received_heads.lock().unwrap().clone()
+    }
+}
+
+impl Drop for MockTunnelProxy {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn tunnel(mut stream: TcpStream, received_heads: Arc<Mutex<Vec<String>>>) {
+    let mut head = Vec::new();
+    let mut buf = [0; 1024];
+    while !head.ends_with(b"\r\n\r\n") {
+        match stream.read(&mut buf).await {
+            Ok(0) | Err(_) => return,
+            Ok(read) => head.extend_from_slice(&buf[..read]),
+        }
+    }
+    let head = String::from_utf8_lossy(&head).to_string();
+    received_heads.lock().unwrap().push(head.clone());
+
+    let authority = head
+        .lines()
+        .next()
+        .and_then(|line| line.strip_prefix("CONNECT "))
+        .and_then(|line| line.split_whitespace().next())
+        .unwrap_or_default();
+    let Ok(mut target) = TcpStream::connect(authority).await else {
+        stream
+            .write_all(b"HTTP/1.1 502 Bad Gateway\r\n\r\n")
+            .await
+            .ok();
+        return;
+    };
+
+    stream
+        .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+        .await
+        .ok();
+    tokio::io::copy_bidirectional(&mut stream, &mut target)
+        .await
+        .ok();
+}
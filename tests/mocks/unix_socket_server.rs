@@ -0,0 +1,121 @@
+use std::convert::Infallible;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use hyper::server::conn::Http;
+use hyper::service::service_fn;
+use hyper::{Body, Request, Response};
+use tempfile::TempDir;
+use tokio::net::UnixListener;
+use tokio::task::JoinHandle;
+
+use super::http_server::{MockResponse, ReceivedRequest};
+
+type Handler = dyn Fn(&ReceivedRequest) -> MockResponse + Send + Sync;
+
+/// HTTP/1.1 server of hyper bound to a Unix socket in a temporary directory, answering
+/// every request with the response built by the given handler
+pub struct MockUnixSocketServer {
+    pub socket_path: PathBuf,
+    received_requests: Arc<Mutex<Vec<ReceivedRequest>>>,
+    task: JoinHandle<()>,
+    _dir: TempDir,
+}
+
+impl MockUnixSocketServer {
+    pub async fn start(
+        handler: impl Fn(&ReceivedRequest) -> MockResponse + Send + Sync + 'static,
+    ) -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let socket_path = dir.path().join("treq.sock");
+        let listener = UnixListener::bind(&socket_path).unwrap();
+        let received_requests = Arc::new(Mutex::new(Vec::new()));
+        let handler: Arc<Handler> = Arc::new(handler);
+
+        let task = {
+            let received_requests = received_requests.clone();
+            tokio::spawn(async move {
+                while let Ok((stream, _)) = listener.accept().await {
+                    let received_requests = received_requests.clone();
+                    let handler = handler.clone();
+                    tokio::spawn(async move {
+                        let service = service_fn(move |req: Request<Body>| {
+                            let received_requests = received_requests.clone();
+                            let handler = handler.clone();
+                            async move {
+                                let request = received_request(req).await;
+                                let response = handler(&request);
+                                received_requests.lock().unwrap().push(request);
+                                Ok::<_, Infallible>(hyper_response(response).await)
+                            }
+                        });
+                        Http::new()
+                            .http1_only(true)
+                            .serve_connection(stream, service)
+                            .await
+                            .ok();
+                    });
+                }
+            })
+        };
+
+        Self {
+            socket_path,
+            received_requests,
+            task,
+            _dir: dir,
+        }
+    }
+
+    pub fn socket_path(&self) -> String {
+        self.socket_path.to_string_lossy().to_string()
+    }
+
+    pub fn received_requests(&self) -> Vec<ReceivedRequest> {
+        self.received_requests.lock().unwrap().clone()
+    }
+}
+
+impl Drop for MockUnixSocketServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+async fn received_request(req: Request<Body>) -> ReceivedRequest {
+    let method = req.method().to_string();
+    let path = req.uri().to_string();
+    let headers = req
+        .headers()
+        .iter()
+        .map(|(key, value)| {
+            (
+                key.to_string(),
+                value.to_str().unwrap_or_default().to_string(),
+            )
+        })
+        .collect();
+    let body = hyper::body::to_bytes(req.into_body())
+        .await
+        .unwrap_or_default()
+        .to_vec();
+
+    ReceivedRequest {
+        method,
+        path,
+        headers,
+        body,
+    }
+}
+
+async fn hyper_response(response: MockResponse) -> Response<Body> {
+    if let Some(delay) = response.delay {
+        tokio::time::sleep(delay).await;
+    }
+
+    let mut builder = Response::builder().status(response.status);
+    for (key, value) in response.headers {
+        builder = builder.header(key, value);
+    }
+    builder.body(Body::from(response.body)).unwrap()
+}
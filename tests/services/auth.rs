@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use tempfile::tempdir;
+use treq::app::backend::Backend;
+use treq::app::services::auth::entities::OAuth2Profile;
+use treq::app::services::request::entities::auth::{Auth, AuthType};
+use treq::app::services::request::entities::requests::RequestData;
+
+use crate::mocks::http_server::{MockHttpServer, MockResponse};
+use crate::utils::factory_provider::create_provider_with_oauth2_profiles;
+
+/// Token endpoint giving a new token on each request ("token-1", "token-2", ...)
+async fn start_token_server(expires_in: u64) -> MockHttpServer {
+    let issued_tokens = Arc::new(AtomicUsize::new(0));
+    MockHttpServer::start(move |_| {
+        let token = issued_tokens.fetch_add(1, Ordering::SeqCst) + 1;
+        MockResponse::new(200)
+            .with_header("Content-Type", "application/json")
+            .with_body(format!(
+                r#"{{ "access_token": "token-{token}", "token_type": "Bearer", "expires_in": {expires_in} }}"#
+            ))
+    })
+    .await
+}
+
+/// API accepting only the given token
+async fn start_api_server(accepted_token: &'static str) -> MockHttpServer {
+    MockHttpServer::start(move |req| {
+        match req.header("authorization") == Some(&format!("Bearer {accepted_token}")) {
+            true => MockResponse::new(200).with_body("Ok"),
+            false => MockResponse::new(401),
+        }
+    })
+    .await
+}
+
+fn profiles(token_server: &MockHttpServer) -> HashMap<String, OAuth2Profile> {
+    HashMap::from([(
+        "api".to_string(),
+        OAuth2Profile {
+            token_url: token_server.url("/token"),
+            client_id: "app".into(),
+            client_secret: "secret".into(),
+            scopes: vec!["read".into()],
+        },
+    )])
+}
+
+async fn submit_with_profile(
+    provider: &mut impl Backend,
+    api_server: &MockHttpServer,
+) -> anyhow::Result<i32> {
+    let request = RequestData::default()
+        .with_url(api_server.url("/users"))
+        .with_auth(Auth::init(AuthType::OAuth2, "api"));
+    let id = provider.add_request(request).await?;
+    let response = provider.submit_request_blocking(id).await?;
+    Ok(response.status)
+}
+
+fn write_cached_token(data_dir: &Path, content: &str) {
+    std::fs::create_dir_all(data_dir.join("oauth2_tokens")).unwrap();
+    std::fs::write(data_dir.join("oauth2_tokens/api"), content).unwrap();
+}
+
+#[tokio::test]
+async fn test_fetch_and_cache_oauth2_token() -> anyhow::Result<()> {
+    let data_dir = tempdir()?;
+    let token_server = start_token_server(3600).await;
+    let api_server = start_api_server("token-1").await;
+
+    let mut provider =
+        create_provider_with_oauth2_profiles(profiles(&token_server), data_dir.path()).await;
+    assert_eq!(200, submit_with_profile(&mut provider, &api_server).await?);
+    assert_eq!(200, submit_with_profile(&mut provider, &api_server).await?);
+
+    // Cached token is kept between runs
+    let mut provider =
+        create_provider_with_oauth2_profiles(profiles(&token_server), data_dir.path()).await;
+    assert_eq!(200, submit_with_profile(&mut provider, &api_server).await?);
+
+    let token_requests = token_server.received_requests();
+    assert_eq!(token_requests.len(), 1);
+    assert_eq!(token_requests[0].method, "POST");
+    assert_eq!(
+        token_requests[0].header("authorization"),
+        Some("Basic YXBwOnNlY3JldA==")
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&token_requests[0].body),
+        "grant_type=client_credentials&scope=read"
+    );
+
+    let cached = std::fs::read_to_string(data_dir.path().join("oauth2_tokens/api"))?;
+    assert!(cached.contains(r#""access_token":"token-1""#));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_renew_expired_oauth2_token() -> anyhow::Result<()> {
+    let data_dir = tempdir()?;
+    let token_server = start_token_server(3600).await;
+    let api_server = start_api_server("token-1").await;
+    write_cached_token(
+        data_dir.path(),
+        r#"{ "access_token": "old", "expires_at": 1000 }"#,
+    );
+
+    let mut provider =
+        create_provider_with_oauth2_profiles(profiles(&token_server), data_dir.path()).await;
+    assert_eq!(200, submit_with_profile(&mut provider, &api_server).await?);
+
+    assert_eq!(token_server.received_requests().len(), 1);
+    assert_eq!(api_server.received_requests().len(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_renew_oauth2_token_rejected_by_server() -> anyhow::Result<()> {
+    let data_dir = tempdir()?;
+    let token_server = start_token_server(3600).await;
+    let api_server = start_api_server("token-1").await;
+    write_cached_token(data_dir.path(), r#"{ "access_token": "revoked" }"#);
+
+    let mut provider =
+        create_provider_with_oauth2_profiles(profiles(&token_server), data_dir.path()).await;
+    assert_eq!(200, submit_with_profile(&mut provider, &api_server).await?);
+
+    let api_requests = api_server.received_requests();
+    assert_eq!(
+        api_requests[0].header("authorization"),
+        Some("Bearer revoked")
+    );
+    assert_eq!(
+        api_requests[1].header("authorization"),
+        Some("Bearer token-1")
+    );
+
+    // A new token rejected is not renewed again
+    let api_server = start_api_server("unknown").await;
+    write_cached_token(data_dir.path(), "");
+    assert_eq!(401, submit_with_profile(&mut provider, &api_server).await?);
+    assert_eq!(token_server.received_requests().len(), 2);
+    assert_eq!(api_server.received_requests().len(), 1);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_error_on_unknown_oauth2_profile() -> anyhow::Result<()> {
+    let data_dir = tempdir()?;
+    let api_server = start_api_server("token-1").await;
+    let mut provider = create_provider_with_oauth2_profiles(HashMap::new(), data_dir.path()).await;
+
+    let error = submit_with_profile(&mut provider, &api_server)
+        .await
+        .unwrap_err();
+
+    assert_eq!(
+        error.to_string(),
+        "OAuth2 profile 'api' is not defined at config file"
+    );
+    assert!(api_server.received_requests().is_empty());
+    assert!(!data_dir.path().join("oauth2_tokens/api").exists());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_error_on_oauth2_profile_name_with_path() -> anyhow::Result<()> {
+    let data_dir = tempdir()?;
+    let api_server = start_api_server("token-1").await;
+    let mut provider = create_provider_with_oauth2_profiles(HashMap::new(), data_dir.path()).await;
+
+    let request = RequestData::default()
+        .with_url(api_server.url("/users"))
+        .with_auth(Auth::init(AuthType::OAuth2, "../config"));
+    let id = provider.add_request(request).await?;
+    let error = provider.submit_request_blocking(id).await.unwrap_err();
+
+    assert!(error
+        .to_string()
+        .starts_with("Invalid OAuth2 profile name '../config'"));
+    assert!(api_server.received_requests().is_empty());
+
+    Ok(())
+}
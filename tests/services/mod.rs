@@ -1,2 +1,5 @@
+pub mod auth;
+pub mod hyper_client_repository;
 pub mod request;
+pub mod unix_socket_requests;
 pub mod web;
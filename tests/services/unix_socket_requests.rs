@@ -0,0 +1,179 @@
+use std::io::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use flate2::write::GzEncoder;
+use treq::app::services::request::entities::methods::METHODS;
+use treq::app::services::request::entities::multipart::MultipartField;
+use treq::app::services::request::entities::redirects::RedirectPolicy;
+use treq::app::services::request::entities::requests::{BodyPayload, RequestData};
+use treq::app::services::request::entities::retries::RetryPolicy;
+use treq::app::services::request::entities::timeouts::Timeouts;
+use treq::app::services::web_client::downloads::DownloadTarget;
+use treq::app::services::web_client::entities::ResponseStage;
+use treq::app::services::web_client::repository_client::hyper_client::HyperClientRepository;
+use treq::app::services::web_client::repository_client::HttpClientRepository;
+
+use crate::mocks::http_server::MockResponse;
+use crate::mocks::unix_socket_server::MockUnixSocketServer;
+
+#[tokio::test]
+async fn test_submit_request_by_unix_socket() -> anyhow::Result<()> {
+    let server = MockUnixSocketServer::start(|_| {
+        MockResponse::new(200)
+            .with_header("Content-Type", "application/json")
+            .with_body(r#"[{"Id":"4a1b"}]"#)
+    })
+    .await;
+    let repository = HyperClientRepository::default();
+
+    let request = RequestData::default()
+        .with_url("http://docker/containers/json?all=true")
+        .with_headers([("Accept".to_string(), "application/json".to_string())])
+        .with_unix_socket(server.socket_path());
+    let response = repository.submit_request(request).await??;
+
+    assert_eq!(response.status, 200);
+    assert_eq!(response.version.as_deref(), Some("HTTP/1.1"));
+    assert_eq!(response.header("content-type"), Some("application/json"));
+    assert_eq!(response.body, r#"[{"Id":"4a1b"}]"#);
+    assert_eq!(response.remote_addr, Some(server.socket_path()));
+
+    let received = &server.received_requests()[0];
+    assert_eq!(received.method, "GET");
+    assert_eq!(received.path, "/containers/json?all=true");
+    assert_eq!(received.header("host"), Some("docker"));
+    assert_eq!(received.header("accept"), Some("application/json"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_send_body_and_decode_response_by_unix_socket() -> anyhow::Result<()> {
+    let server = MockUnixSocketServer::start(|_| {
+        let mut gzip = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(b"Created").unwrap();
+        MockResponse::new(201)
+            .with_header("Content-Encoding", "gzip")
+            .with_body(gzip.finish().unwrap())
+    })
+    .await;
+    let repository = HyperClientRepository::default();
+
+    let request = RequestData::default()
+        .with_url("http://localhost:2375/containers/create")
+        .with_method(METHODS::POST)
+        .with_body_payload(BodyPayload::Raw(r#"{"Image":"alpine"}"#.into()))
+        .with_unix_socket(server.socket_path());
+    let response = repository.submit_request(request).await??;
+
+    assert_eq!(response.status, 201);
+    assert_eq!(response.body, "Created");
+    assert!(response.wire_size.is_some());
+
+    let received = &server.received_requests()[0];
+    assert_eq!(received.method, "POST");
+    assert_eq!(received.header("host"), Some("localhost:2375"));
+    assert_eq!(received.body, br#"{"Image":"alpine"}"#);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_unix_socket_request_timeout() -> anyhow::Result<()> {
+    let server = MockUnixSocketServer::start(|_| {
+        MockResponse::new(200).with_delay(Duration::from_millis(500))
+    })
+    .await;
+    let repository = HyperClientRepository::default();
+
+    let request = RequestData::default()
+        .with_url("http://localhost/slow")
+        .with_timeouts(Timeouts::default().with_total_ms(50))
+        .with_unix_socket(server.socket_path());
+    let response = repository.submit_request(request).await??;
+
+    assert_eq!(response.stage, ResponseStage::TimedOut);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_follow_redirects_and_retry_by_unix_socket() -> anyhow::Result<()> {
+    let attempts = AtomicUsize::new(0);
+    let server = MockUnixSocketServer::start(move |req| match req.path.as_str() {
+        "/old" => MockResponse::new(302).with_header("Location", "/new"),
+        _ if attempts.fetch_add(1, Ordering::SeqCst) == 0 => MockResponse::new(503),
+        _ => MockResponse::new(200).with_body("Moved"),
+    })
+    .await;
+    let repository = HyperClientRepository::default();
+
+    let request = RequestData::default()
+        .with_url("http://localhost/old")
+        .with_redirects(RedirectPolicy::default().with_follow(true))
+        .with_retries(RetryPolicy::default().with_retries(1).with_backoff_ms(10))
+        .with_unix_socket(server.socket_path());
+    let response = repository.submit_request(request).await??;
+
+    assert_eq!(response.status, 200);
+    assert_eq!(response.body, "Moved");
+    assert_eq!(response.attempts, 2);
+    assert_eq!(response.redirect_history.len(), 1);
+    assert_eq!(response.proxy, None);
+
+    let paths: Vec<String> = server
+        .received_requests()
+        .into_iter()
+        .map(|req| req.path)
+        .collect();
+    assert_eq!(paths, ["/old", "/new", "/old", "/new"]);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_download_and_send_multipart_by_unix_socket() -> anyhow::Result<()> {
+    let server =
+        MockUnixSocketServer::start(|_| MockResponse::new(200).with_body("file content")).await;
+    let repository = HyperClientRepository::default();
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("file.txt");
+
+    let request = RequestData::default()
+        .with_url("http://localhost/upload")
+        .with_method(METHODS::POST)
+        .with_body_payload(BodyPayload::Multipart(vec![MultipartField::Text {
+            name: "name".into(),
+            value: "alpine".into(),
+        }]))
+        .with_unix_socket(server.socket_path());
+    let target = DownloadTarget::default().with_path(&path);
+    let response = repository.download_request(request, target).await??;
+
+    assert_eq!(response.download.unwrap().bytes_written, 12);
+    assert_eq!(std::fs::read_to_string(&path)?, "file content");
+
+    let received = &server.received_requests()[0];
+    assert!(received
+        .header("content-type")
+        .is_some_and(|value| value.starts_with("multipart/form-data; boundary=")));
+    assert!(String::from_utf8_lossy(&received.body).contains("alpine"));
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_error_on_missing_unix_socket() -> anyhow::Result<()> {
+    let repository = HyperClientRepository::default();
+    let request = RequestData::default()
+        .with_url("http://localhost/")
+        .with_unix_socket("/tmp/treq-missing.sock");
+
+    let err = repository.submit_request(request).await?.unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("Could not connect to Unix socket '/tmp/treq-missing.sock'"));
+
+    Ok(())
+}
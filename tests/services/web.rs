@@ -1,17 +1,30 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tempfile::tempdir;
+use tokio::sync::oneshot;
 use treq::app::backend::Backend;
-use treq::app::services::request::entities::requests::RequestData;
+use treq::app::services::request::entities::auth::{Auth, AuthType};
+use treq::app::services::request::entities::body_files::BodyFileReference;
+use treq::app::services::request::entities::methods::METHODS;
+use treq::app::services::request::entities::requests::{BodyPayload, RequestData};
 use treq::app::services::web_client::entities::Response;
+use treq::app::services::web_client::facade::WebClientFacade;
+use treq::app::services::web_client::repository_client::hyper_client::HyperClientRepository;
 use treq::app::services::web_client::repository_client::MockHttpClientRepository;
+use treq::app::services::web_client::service::WebClient;
 
+use crate::mocks::http_server::{MockHttpServer, MockResponse};
 use crate::utils::factory_provider::create_provider_with_mock_web_client;
 
 #[tokio::test]
 async fn test_basic_call_get() {
     fn expected_response() -> Response {
-        let mut resp = Response::default();
-        resp.status = 200;
-        resp.body = "Ok".into();
-        resp
+        Response {
+            status: 200,
+            body: "Ok".into(),
+            ..Default::default()
+        }
     }
 
     let mut mock_client = MockHttpClientRepository::new();
@@ -27,3 +40,126 @@ async fn test_basic_call_get() {
 
     assert_eq!(response_submit, expected_response());
 }
+
+#[tokio::test]
+async fn test_abort_request_when_its_response_is_dropped() -> anyhow::Result<()> {
+    let (tx_aborted, rx_aborted) = oneshot::channel::<()>();
+    let tx_aborted = Mutex::new(Some(tx_aborted));
+
+    let mut mock_client = MockHttpClientRepository::new();
+    mock_client
+        .expect_submit_request()
+        .times(1)
+        .returning(move |_| {
+            // Dropped with the task, only when it's aborted
+            let tx_aborted = tx_aborted.lock().unwrap().take();
+            tokio::task::spawn(async move {
+                let _tx_aborted = tx_aborted;
+                std::future::pending::<()>().await;
+                Ok(Response::default())
+            })
+        });
+
+    let mut provider = create_provider_with_mock_web_client(mock_client).await;
+    let id_req = provider.add_request(RequestData::default()).await?;
+
+    let response_submit = provider.submit_request_async(id_req, None).await?;
+    drop(response_submit);
+
+    let aborted = tokio::time::timeout(Duration::from_secs(1), rx_aborted).await?;
+    assert!(aborted.is_err());
+
+    provider.close().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_submit_request_by_unix_socket_with_same_client() -> anyhow::Result<()> {
+    let mut mock_client = MockHttpClientRepository::new();
+    mock_client
+        .expect_submit_request()
+        .withf(|request| request.unix_socket.as_deref() == Some("/var/run/docker.sock"))
+        .times(1)
+        .returning(|_| {
+            tokio::task::spawn(async {
+                Ok(Response {
+                    status: 200,
+                    ..Default::default()
+                })
+            })
+        });
+
+    let mut web_client = WebClient::init(mock_client);
+
+    let request = RequestData::default()
+        .with_url("http://docker/version")
+        .with_unix_socket("/var/run/docker.sock");
+    let response = web_client.submit_async(request, None).await??;
+    assert_eq!(response.status, 200);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_sign_request_with_aws_credentials_of_profile() -> anyhow::Result<()> {
+    let credentials_dir = tempdir()?;
+    let credentials_file = credentials_dir.path().join("credentials");
+    std::fs::write(
+        &credentials_file,
+        "[treq-test]\naws_access_key_id = minioadmin\naws_secret_access_key = minio-secret\n",
+    )?;
+    std::env::set_var("AWS_SHARED_CREDENTIALS_FILE", &credentials_file);
+
+    let server = MockHttpServer::start(|_| MockResponse::new(200)).await;
+    let mut web_client = WebClient::init(HyperClientRepository::default());
+
+    let request = RequestData::default()
+        .with_url(server.url("/bucket/notes.txt"))
+        .with_method(METHODS::PUT)
+        .with_body_payload(BodyPayload::Raw("Hello".into()))
+        .with_auth(Auth::init(AuthType::AwsSigV4, "us-east-1:s3:treq-test"));
+    web_client.submit_async(request, None).await??;
+
+    let received = &server.received_requests()[0];
+    assert_eq!(received.body, b"Hello");
+    assert!(received.header("x-amz-date").is_some());
+    assert_eq!(
+        received.header("x-amz-content-sha256"),
+        Some("185f8db32271fe25f561a6fc938b2e264306ec304eda518007d1764826381969")
+    );
+    assert!(received
+        .header("authorization")
+        .unwrap()
+        .starts_with("AWS4-HMAC-SHA256 Credential=minioadmin/"));
+
+    // Body files are read before signing
+    let body_file = credentials_dir.path().join("notes.txt");
+    std::fs::write(&body_file, "Hello")?;
+    let request = RequestData::default()
+        .with_url(server.url("/bucket/notes.txt"))
+        .with_method(METHODS::PUT)
+        .with_body_files(vec![BodyFileReference {
+            keys: Vec::new(),
+            path: body_file.to_string_lossy().to_string(),
+            as_json: false,
+        }])
+        .with_auth(Auth::init(AuthType::AwsSigV4, "us-east-1:s3:treq-test"));
+    web_client.submit_async(request, None).await??;
+
+    let received = &server.received_requests()[1];
+    assert_eq!(received.body, b"Hello");
+    assert_eq!(
+        received.header("x-amz-content-sha256"),
+        Some("185f8db32271fe25f561a6fc938b2e264306ec304eda518007d1764826381969")
+    );
+
+    // Unknown profile fails before sending anything
+    let request = RequestData::default()
+        .with_url(server.url("/bucket/notes.txt"))
+        .with_auth(Auth::init(AuthType::AwsSigV4, "us-east-1:s3:unknown"));
+    let error = web_client.submit_async(request, None).await?.unwrap_err();
+    assert!(error.to_string().starts_with("No AWS credentials found"));
+    assert_eq!(server.received_requests().len(), 2);
+
+    Ok(())
+}
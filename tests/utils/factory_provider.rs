@@ -1,15 +1,21 @@
+use std::collections::HashMap;
+use std::path::Path;
+
 use treq::app::backend::AppBackend;
+use treq::app::services::auth::entities::OAuth2Profile;
+use treq::app::services::auth::service::AuthService;
 use treq::app::services::files::service::FileService;
 use treq::app::services::request::service::RequestService;
-use treq::app::services::web_client::repository_client::reqwest::ReqwestClientRepository;
+use treq::app::services::web_client::repository_client::hyper_client::HyperClientRepository;
 use treq::app::services::web_client::repository_client::HttpClientRepository;
 use treq::app::services::web_client::service::WebClient;
 
 pub async fn create_default_provider() -> AppBackend {
     let req = RequestService::init();
-    let web = WebClient::init(ReqwestClientRepository);
+    let web = WebClient::init(HyperClientRepository::default());
     let files = FileService::init("", "", "");
-    AppBackend::init(req, web, files)
+    let auth = AuthService::init(HashMap::new(), HyperClientRepository::default());
+    AppBackend::init(req, web, files, auth)
 }
 
 pub async fn create_provider_with_mock_web_client(
@@ -18,5 +24,17 @@ pub async fn create_provider_with_mock_web_client(
     let req = RequestService::init();
     let web = WebClient::init(web);
     let files = FileService::init("", "", "");
-    AppBackend::init(req, web, files)
+    let auth = AuthService::init(HashMap::new(), HyperClientRepository::default());
+    AppBackend::init(req, web, files, auth)
+}
+
+pub async fn create_provider_with_oauth2_profiles(
+    profiles: HashMap<String, OAuth2Profile>,
+    data_dir: &Path,
+) -> AppBackend {
+    let req = RequestService::init();
+    let web = WebClient::init(HyperClientRepository::default());
+    let files = FileService::init("", data_dir, "");
+    let auth = AuthService::init(profiles, HyperClientRepository::default());
+    AppBackend::init(req, web, files, auth)
 }